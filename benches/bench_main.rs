@@ -1,4 +1,4 @@
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
 use pci_id::pci_ids::{PciIds, PATH_TO_PCI_IDS};
 use std::path::Path;
 
@@ -33,9 +33,137 @@ pub fn bench_parse_classes(c: &mut Criterion) {
         })
     });
 }
+
+pub fn bench_with_capacity(c: &mut Criterion) {
+    let data = std::fs::read_to_string(PATH_TO_PCI_IDS).unwrap();
+    c.bench_function("parse all with estimate", |b| {
+        b.iter(|| PciIds::parse_from_str(&data).unwrap())
+    });
+    // The reader doesn't know the length up front, so its lists grow as they fill up
+    c.bench_function("parse all without estimate", |b| {
+        b.iter(|| PciIds::parse_from_reader(data.as_bytes()).unwrap())
+    });
+    c.bench_function("parse vendors into new", |b| {
+        b.iter(|| {
+            let mut pci_ids = PciIds::new();
+            pci_ids.parse_vendors_from_str(&data).unwrap();
+            pci_ids
+        })
+    });
+    c.bench_function("parse vendors into with_capacity", |b| {
+        b.iter(|| {
+            let mut pci_ids = PciIds::with_capacity(3000, 0);
+            pci_ids.parse_vendors_from_str(&data).unwrap();
+            pci_ids
+        })
+    });
+}
+
+#[cfg(feature = "mmap")]
+pub fn bench_parse_mmap(c: &mut Criterion) {
+    c.bench_function("parse all read_to_string", |b| {
+        b.iter(|| PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap())
+    });
+    c.bench_function("parse all mmap", |b| {
+        b.iter(|| PciIds::parse_mmap(Path::new(PATH_TO_PCI_IDS)).unwrap())
+    });
+}
+
+/// Parse a file of several megabytes, made of copies of the real vendor section, both ways.
+#[cfg(feature = "parallel")]
+pub fn bench_parse_parallel(c: &mut Criterion) {
+    let data = std::fs::read_to_string(PATH_TO_PCI_IDS).unwrap();
+    let vendors = &data[..data.find("\nC ").unwrap() + 1];
+    let data = vendors.repeat(8);
+    c.bench_function("parse large sequential", |b| {
+        b.iter(|| PciIds::parse_from_str(&data).unwrap())
+    });
+    c.bench_function("parse large parallel", |b| {
+        b.iter(|| PciIds::parse_from_str_parallel(&data).unwrap())
+    });
+}
+
+/// The benchmarks of optional features, as far as they are enabled.
+pub fn bench_features(c: &mut Criterion) {
+    #[cfg(feature = "mmap")]
+    bench_parse_mmap(c);
+    #[cfg(feature = "parallel")]
+    bench_parse_parallel(c);
+    let _ = c;
+}
+
+pub fn bench_intern(c: &mut Criterion) {
+    let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+    let before = pci_ids.approximate_memory_usage();
+    let saved = pci_ids.clone().intern();
+    println!(
+        "intern: {before} bytes before, {} after, {saved} saved",
+        before - saved
+    );
+    c.bench_function("intern", |b| {
+        b.iter_batched(
+            || pci_ids.clone(),
+            |mut pci_ids| pci_ids.intern(),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+/// Pseudo random vendor and device ids, half of them taken from the database so both hits and
+/// misses are measured.
+fn lookup_ids(pci_ids: &PciIds) -> Vec<(u16, u16)> {
+    let devices: Vec<(u16, u16)> = pci_ids
+        .vendors()
+        .iter()
+        .flat_map(|v| v.devices().iter().map(move |d| (v.id(), d.id())))
+        .collect();
+    let mut state: u32 = 0x1234_5678;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+    (0..1000)
+        .map(|i| {
+            let random = next();
+            if i % 2 == 0 {
+                devices[random as usize % devices.len()]
+            } else {
+                ((random >> 16) as u16, random as u16)
+            }
+        })
+        .collect()
+}
+
+pub fn bench_lookup(c: &mut Criterion) {
+    let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+    let ids = lookup_ids(&pci_ids);
+    c.bench_function("linear lookup", |b| {
+        b.iter(|| {
+            for &(vendor, device) in &ids {
+                black_box(
+                    pci_ids
+                        .vendor_by_id(vendor)
+                        .and_then(|v| v.device_by_id(device)),
+                );
+            }
+        })
+    });
+    let index = pci_ids.index();
+    c.bench_function("indexed lookup", |b| {
+        b.iter(|| {
+            for &(vendor, device) in &ids {
+                black_box(index.get_device(vendor, device));
+            }
+        })
+    });
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default();
-    targets = bench_parse_all, bench_parse_vendors, bench_parse_classes
+    targets = bench_parse_all, bench_parse_vendors, bench_parse_classes, bench_lookup, bench_intern,
+        bench_with_capacity, bench_features
 }
 criterion_main!(benches);
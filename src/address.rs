@@ -0,0 +1,158 @@
+//! Addresses of PCI devices on the bus, like `0000:03:00.0`.
+//!
+//! ```
+//! use pci_id::address::PciAddress;
+//!
+//! let address: PciAddress = "03:00.0".parse().unwrap();
+//! assert_eq!(address.bus, 0x03);
+//! assert_eq!(address.to_string(), "0000:03:00.0");
+//! ```
+
+use alloc::borrow::ToOwned;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::pci_ids::ParseError;
+
+/// Location of a PCI function, written as `domain:bus:device.function` in hex.
+///
+/// Addresses are ordered by domain, bus, device and then function, i.e. by slot.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PciAddress {
+    /// PCI segment, `0000` on most systems.
+    pub domain: u16,
+    /// Bus number.
+    pub bus: u8,
+    /// Device number on the bus, up to `1f`.
+    pub device: u8,
+    /// Function number of the device, up to `7`.
+    pub function: u8,
+}
+
+impl FromStr for PciAddress {
+    type Err = ParseError;
+
+    /// Parse a full `0000:03:00.0` or short `03:00.0` address, the short form being in domain 0.
+    /// Digits may be uppercase, but unlike ids, the parts take no `0x` prefix, sign or
+    /// surrounding whitespace.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || ParseError::MalformedAddress {
+            address: s.to_owned(),
+        };
+        let (slot, function) = s.rsplit_once('.').ok_or_else(malformed)?;
+        let (rest, device) = slot.rsplit_once(':').ok_or_else(malformed)?;
+        let (domain, bus) = match rest.rsplit_once(':') {
+            Some((domain, _)) if domain.contains(':') => return Err(malformed()),
+            Some((domain, bus)) => (parse_part(domain, 4).ok_or_else(malformed)?, bus),
+            None => (0, rest),
+        };
+        let narrow = |part: &str, max_digits: usize, max: u8| {
+            parse_part(part, max_digits)
+                .and_then(|value| u8::try_from(value).ok())
+                .filter(|&value| value <= max)
+                .ok_or_else(malformed)
+        };
+        Ok(Self {
+            domain,
+            bus: narrow(bus, 2, u8::MAX)?,
+            device: narrow(device, 2, 0x1f)?,
+            function: narrow(function, 1, 0x07)?,
+        })
+    }
+}
+
+/// Parse one part of an address, made of one up to `max_digits` hex digits and nothing else.
+fn parse_part(part: &str, max_digits: usize) -> Option<u16> {
+    if !(1..=max_digits).contains(&part.len()) || !part.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u16::from_str_radix(part, 16).ok()
+}
+
+impl fmt::Display for PciAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04x}:{:02x}:{:02x}.{:x}",
+            self.domain, self.bus, self.device, self.function
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::address::PciAddress;
+    use crate::pci_ids::ParseError;
+
+    /// Test parsing of the full and the short form
+    #[test]
+    fn test_parse_address() {
+        let full: PciAddress = "0001:03:1f.7".parse().unwrap();
+        assert_eq!(
+            full,
+            PciAddress {
+                domain: 0x0001,
+                bus: 0x03,
+                device: 0x1f,
+                function: 0x7
+            }
+        );
+        let short: PciAddress = "0A:00.1".parse().unwrap();
+        assert_eq!(
+            short,
+            PciAddress {
+                domain: 0,
+                bus: 0x0a,
+                device: 0x00,
+                function: 0x1
+            }
+        );
+        assert!(short < full);
+
+        for input in [
+            "",
+            "03:00",
+            "03.0",
+            "0000:03:20.0",
+            "03:00.8",
+            "100:00.0",
+            "0:0:0:0.0",
+            "0g:00.0",
+            "0x0: +3 :0x1f.0x7",
+            "+3:00.0",
+            " 03:00.0",
+            ":03:00.0",
+            "03::0",
+            "00000:03:00.0",
+            "003:00.0",
+            "03:000.0",
+            "03:00.00",
+        ] {
+            assert!(
+                matches!(
+                    input.parse::<PciAddress>(),
+                    Err(ParseError::MalformedAddress { .. })
+                ),
+                "{input:?}"
+            );
+        }
+    }
+
+    /// Test that printed addresses use the full form and parse back to the same address
+    #[test]
+    fn test_address_round_trip() {
+        for input in [
+            "0000:00:00.0",
+            "0000:03:00.0",
+            "ffff:ff:1f.7",
+            "0002:0a:1c.4",
+        ] {
+            let address: PciAddress = input.parse().unwrap();
+            assert_eq!(address.to_string(), input);
+        }
+        let short: PciAddress = "03:00.0".parse().unwrap();
+        assert_eq!(short.to_string(), "0000:03:00.0");
+        assert_eq!(short.to_string().parse::<PciAddress>().unwrap(), short);
+    }
+}
@@ -0,0 +1,383 @@
+//! A variant of [PciIds] that borrows every name from the parsed text instead of copying it.
+//!
+//! Pays off for files that live as long as the program anyway, like one embedded with
+//! `include_str!`:
+//! ```
+//! use pci_id::borrowed::PciIdsRef;
+//!
+//! static PCI_IDS: &str = "10de  NVIDIA Corporation\n\t1b80  GP104 [GeForce GTX 1080]\n";
+//!
+//! let pci_ids = PciIdsRef::parse_from_str(PCI_IDS).unwrap();
+//! let nvidia: &'static str = pci_ids.vendor_by_id(0x10de).unwrap().name();
+//! assert_eq!(nvidia, "NVIDIA Corporation");
+//! ```
+
+use alloc::borrow::ToOwned;
+use alloc::vec::Vec;
+
+use crate::class::{Class, Interface, SubClass};
+use crate::pci_ids::{split_inline_comment, Classifier, Entry, LineClassifier, ParseError, PciIds};
+use crate::vendor::{Device, SubDevice, Vendor};
+
+/// The vendors and classes of a pci.ids file, with names borrowed from the text they were parsed
+/// from.
+///
+/// Holds the same entries as [PciIds::parse_from_str] would, minus the comments and the header.
+/// Convert it with [PciIdsRef::to_owned] where the owned types are needed.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct PciIdsRef<'a> {
+    vendors: Vec<VendorRef<'a>>,
+    classes: Vec<ClassRef<'a>>,
+}
+
+/// A vendor borrowing its name, see [Vendor].
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct VendorRef<'a> {
+    id: u16,
+    name: &'a str,
+    devices: Vec<DeviceRef<'a>>,
+}
+
+/// A device borrowing its name, see [Device].
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct DeviceRef<'a> {
+    id: u16,
+    name: &'a str,
+    subdevices: Vec<SubDeviceRef<'a>>,
+}
+
+/// A subdevice borrowing its name, see [SubDevice].
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct SubDeviceRef<'a> {
+    subvendor_id: u16,
+    subdevice_id: u16,
+    name: &'a str,
+}
+
+/// A class of devices along with its subclasses, see [Class]. Its name comes from
+/// [DeviceClass](crate::device_class::DeviceClass) rather than the file.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct ClassRef<'a> {
+    id: u8,
+    subclasses: Vec<SubClassRef<'a>>,
+}
+
+/// A subclass borrowing its name, see [SubClass].
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct SubClassRef<'a> {
+    id: u8,
+    name: &'a str,
+    interfaces: Vec<InterfaceRef<'a>>,
+}
+
+/// A programming interface borrowing its name, see [Interface].
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct InterfaceRef<'a> {
+    id: u8,
+    name: &'a str,
+}
+
+impl<'a> PciIdsRef<'a> {
+    /// Parse the contents of a pci.ids file, borrowing every name from `data`.
+    ///
+    /// # Errors
+    /// A malformed line is reported as a [ParseError] carrying its line number, just like with
+    /// [PciIds::parse_from_str].
+    pub fn parse_from_str(data: &'a str) -> Result<Self, ParseError> {
+        let mut pci_ids = Self::default();
+        let mut classifier = Classifier::default();
+        for (index, line) in data.lines().enumerate() {
+            let line_number = index + 1;
+            if let Some(entry) = classifier.classify(line_number, line)? {
+                pci_ids.push(line_number, entry)?;
+            }
+        }
+        Ok(pci_ids)
+    }
+
+    /// Add an entry below the last entry of the level above it.
+    fn push(&mut self, line_number: usize, entry: Entry<'a>) -> Result<(), ParseError> {
+        let unexpected_indent = || ParseError::UnexpectedIndent { line_number };
+        match entry {
+            Entry::Comment(_) => {}
+            Entry::Vendor { id, name } => self.vendors.push(VendorRef {
+                id,
+                name: split_inline_comment(name).0,
+                devices: Vec::new(),
+            }),
+            Entry::Device { id, name } => {
+                let vendor = self.vendors.last_mut().ok_or_else(unexpected_indent)?;
+                vendor.devices.push(DeviceRef {
+                    id,
+                    name: split_inline_comment(name).0,
+                    subdevices: Vec::new(),
+                });
+            }
+            Entry::SubDevice {
+                subvendor_id,
+                subdevice_id,
+                name,
+            } => {
+                let device = self
+                    .vendors
+                    .last_mut()
+                    .and_then(|v| v.devices.last_mut())
+                    .ok_or(ParseError::OrphanSubdevice { line_number })?;
+                device.subdevices.push(SubDeviceRef {
+                    subvendor_id,
+                    subdevice_id,
+                    name: split_inline_comment(name).0,
+                });
+            }
+            Entry::Class { id, .. } => self.classes.push(ClassRef {
+                id,
+                subclasses: Vec::new(),
+            }),
+            Entry::SubClass { id, name } => {
+                // Like with the owned types, a subclass above the first class is dropped
+                if let Some(class) = self.classes.last_mut() {
+                    class.subclasses.push(SubClassRef {
+                        id,
+                        name: split_inline_comment(name).0,
+                        interfaces: Vec::new(),
+                    });
+                }
+            }
+            Entry::Interface { id, name } => {
+                let subclass = self
+                    .classes
+                    .last_mut()
+                    .and_then(|c| c.subclasses.last_mut())
+                    .ok_or_else(unexpected_indent)?;
+                subclass.interfaces.push(InterfaceRef {
+                    id,
+                    name: split_inline_comment(name).0,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// List of vendors, in file order.
+    pub fn vendors(&self) -> &[VendorRef<'a>] {
+        &self.vendors
+    }
+
+    /// List of classes, in file order.
+    pub fn classes(&self) -> &[ClassRef<'a>] {
+        &self.classes
+    }
+
+    /// Returns the first vendor with the given id, like [PciIds::vendor_by_id].
+    pub fn vendor_by_id(&self, id: u16) -> Option<&VendorRef<'a>> {
+        self.vendors.iter().find(|v| v.id == id)
+    }
+
+    /// Returns the first class with the given id, like [PciIds::class_by_id].
+    pub fn class_by_id(&self, id: u8) -> Option<&ClassRef<'a>> {
+        self.classes.iter().find(|c| c.id == id)
+    }
+
+    /// Copy every entry into the owned types of a [PciIds].
+    pub fn to_owned(&self) -> PciIds {
+        let vendors = self.vendors.iter().map(VendorRef::to_owned).collect();
+        let classes = self.classes.iter().map(ClassRef::to_owned).collect();
+        PciIds::from_parts(vendors, classes)
+    }
+}
+
+impl<'a> VendorRef<'a> {
+    /// Id of the vendor.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// Name of the vendor, borrowed from the parsed text.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// List of devices of the vendor, in file order.
+    pub fn devices(&self) -> &[DeviceRef<'a>] {
+        &self.devices
+    }
+
+    /// Returns the first device with the given id, like [Vendor::device_by_id].
+    pub fn device_by_id(&self, id: u16) -> Option<&DeviceRef<'a>> {
+        self.devices.iter().find(|d| d.id == id)
+    }
+
+    /// Copy the vendor and its devices into a [Vendor].
+    pub fn to_owned(&self) -> Vendor {
+        let mut vendor = Vendor::new(self.id, self.name.to_owned());
+        vendor.set_devices(self.devices.iter().map(DeviceRef::to_owned).collect());
+        vendor
+    }
+}
+
+impl<'a> DeviceRef<'a> {
+    /// Id of the device.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// Name of the device, borrowed from the parsed text.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// List of subdevices of the device, in file order.
+    pub fn subdevices(&self) -> &[SubDeviceRef<'a>] {
+        &self.subdevices
+    }
+
+    /// Copy the device and its subdevices into a [Device].
+    pub fn to_owned(&self) -> Device {
+        let mut device = Device::new(self.id, self.name.to_owned());
+        device.set_subdevices(self.subdevices.iter().map(SubDeviceRef::to_owned).collect());
+        device
+    }
+}
+
+impl<'a> SubDeviceRef<'a> {
+    /// Id of the subvendor.
+    pub fn subvendor_id(&self) -> u16 {
+        self.subvendor_id
+    }
+
+    /// Id of the subdevice.
+    pub fn subdevice_id(&self) -> u16 {
+        self.subdevice_id
+    }
+
+    /// Name of the subdevice, borrowed from the parsed text.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Copy the subdevice into a [SubDevice].
+    pub fn to_owned(&self) -> SubDevice {
+        SubDevice::new(self.subvendor_id, self.subdevice_id, self.name.to_owned())
+    }
+}
+
+impl<'a> ClassRef<'a> {
+    /// Id of the class.
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// List of subclasses of the class, in file order.
+    pub fn subclasses(&self) -> &[SubClassRef<'a>] {
+        &self.subclasses
+    }
+
+    /// Copy the class and its subclasses into a [Class].
+    pub fn to_owned(&self) -> Class {
+        let mut class = Class::new(self.id);
+        class.set_subclasses(self.subclasses.iter().map(SubClassRef::to_owned).collect());
+        class
+    }
+}
+
+impl<'a> SubClassRef<'a> {
+    /// Id of the subclass.
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// Name of the subclass, borrowed from the parsed text.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// List of programming interfaces of the subclass, in file order.
+    pub fn interfaces(&self) -> &[InterfaceRef<'a>] {
+        &self.interfaces
+    }
+
+    /// Copy the subclass and its interfaces into a [SubClass].
+    pub fn to_owned(&self) -> SubClass {
+        let mut subclass = SubClass::new(self.id, self.name.to_owned());
+        subclass.set_interfaces(self.interfaces.iter().map(InterfaceRef::to_owned).collect());
+        subclass
+    }
+}
+
+impl<'a> InterfaceRef<'a> {
+    /// Id of the programming interface.
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// Name of the programming interface, borrowed from the parsed text.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Copy the programming interface into an [Interface].
+    pub fn to_owned(&self) -> Interface {
+        Interface::new(self.id, self.name.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::borrowed::PciIdsRef;
+    use crate::pci_ids::{ParseError, PciIds};
+
+    static PCI_IDS: &str = include_str!("../data/pci.ids");
+
+    /// Test that the names point into the parsed text, and that converting gives what parsing into
+    /// the owned types gives
+    #[test]
+    fn test_parse_borrowed() {
+        let pci_ids = PciIdsRef::parse_from_str(PCI_IDS).unwrap();
+        let borrows = |name: &str| PCI_IDS.as_bytes().as_ptr_range().contains(&name.as_ptr());
+
+        let compaq = pci_ids.vendor_by_id(0x0e11).unwrap();
+        let smart_array = compaq.device_by_id(0x0046).unwrap();
+        let subdevice = smart_array
+            .subdevices()
+            .iter()
+            .find(|s| (s.subvendor_id(), s.subdevice_id()) == (0x0e11, 0x409a))
+            .unwrap();
+        assert_eq!(smart_array.name(), "Smart Array 64xx");
+        assert_eq!(subdevice.name(), "Smart Array 641");
+        assert!(borrows(compaq.name()) && borrows(smart_array.name()) && borrows(subdevice.name()));
+        for vendor in pci_ids.vendors() {
+            assert!(borrows(vendor.name()));
+            assert!(vendor.devices().iter().all(|d| borrows(d.name())));
+        }
+        let storage = pci_ids.class_by_id(0x01).unwrap();
+        let sata = storage
+            .subclasses()
+            .iter()
+            .find(|s| s.id() == 0x06)
+            .unwrap();
+        assert_eq!(sata.interfaces()[1].name(), "AHCI 1.0");
+        assert!(borrows(sata.interfaces()[1].name()));
+
+        let owned = PciIds::parse_from_str(PCI_IDS).unwrap();
+        let converted = pci_ids.to_owned();
+        assert_eq!(converted.vendors(), owned.vendors());
+        assert_eq!(converted.classes(), owned.classes());
+    }
+
+    /// Test that malformed files fail like with the owned types
+    #[test]
+    fn test_parse_borrowed_errors() {
+        assert!(matches!(
+            PciIdsRef::parse_from_str("\t0046  Smart Array 64xx\n"),
+            Err(ParseError::UnexpectedIndent { line_number: 1 })
+        ));
+        assert!(matches!(
+            PciIdsRef::parse_from_str("0e11  Compaq\n\t\t0e11 409a  Smart Array 641\n"),
+            Err(ParseError::OrphanSubdevice { line_number: 2 })
+        ));
+        assert!(matches!(
+            PciIdsRef::parse_from_str("0e11  Compaq\n\t00zz  Broken\n"),
+            Err(ParseError::InvalidHex { line_number: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,311 @@
+//! Construction of a [PciIds] in code, e.g. for tests or synthetic databases.
+//!
+//! ```
+//! use pci_id::builder::PciIdsBuilder;
+//!
+//! let pci_ids = PciIdsBuilder::new()
+//!     .add_vendor(0x10de, "NVIDIA Corporation")
+//!     .add_device_to(0x10de, 0x1b80, "GP104 [GeForce GTX 1080]")
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(pci_ids.resolve(0x10de, 0x1b80, None, None).device, Some("GP104 [GeForce GTX 1080]"));
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::class::{Class, Interface, SubClass};
+use crate::pci_ids::PciIds;
+use crate::vendor::{Device, SubDevice, Vendor};
+
+/// Errors that can occur when building a [PciIds], all about adding an entry to a parent that
+/// wasn't added before.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum BuildError {
+    /// A device was added to a vendor that doesn't exist.
+    UnknownVendor {
+        /// Id of the missing vendor.
+        vendor_id: u16,
+    },
+    /// A subdevice was added to a device that doesn't exist.
+    UnknownDevice {
+        /// Id of the vendor the device was looked up in.
+        vendor_id: u16,
+        /// Id of the missing device.
+        device_id: u16,
+    },
+    /// A subclass was added to a class that doesn't exist.
+    UnknownClass {
+        /// Id of the missing class.
+        class_id: u8,
+    },
+    /// An interface was added to a subclass that doesn't exist.
+    UnknownSubClass {
+        /// Id of the class the subclass was looked up in.
+        class_id: u8,
+        /// Id of the missing subclass.
+        subclass_id: u8,
+    },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownVendor { vendor_id } => {
+                write!(f, "device added to unknown vendor {vendor_id:04x}")
+            }
+            Self::UnknownDevice {
+                vendor_id,
+                device_id,
+            } => write!(
+                f,
+                "subdevice added to unknown device {vendor_id:04x}:{device_id:04x}"
+            ),
+            Self::UnknownClass { class_id } => {
+                write!(f, "subclass added to unknown class {class_id:02x}")
+            }
+            Self::UnknownSubClass {
+                class_id,
+                subclass_id,
+            } => write!(
+                f,
+                "interface added to unknown subclass {class_id:02x}{subclass_id:02x}"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for BuildError {}
+
+/// A device along with the subdevices to attach to it on build.
+type PendingDevice = (Device, Vec<SubDevice>);
+/// A subclass along with the interfaces to attach to it on build.
+type PendingSubClass = (SubClass, Vec<Interface>);
+
+/// Builder for a [PciIds], wiring up vendors, devices, subdevices, classes, subclasses and
+/// interfaces by the ids of their parents.
+///
+/// Entries keep the order they were added in. If a parent id was added more than once, children
+/// go to the first one, just like lookups find the first one. Errors are reported by
+/// [PciIdsBuilder::build], so calls can be chained.
+#[derive(Debug, Clone, Default)]
+pub struct PciIdsBuilder {
+    vendors: Vec<(Vendor, Vec<PendingDevice>)>,
+    classes: Vec<(Class, Vec<PendingSubClass>)>,
+    error: Option<BuildError>,
+}
+
+impl PciIdsBuilder {
+    /// Create a new builder without any entries.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a vendor.
+    pub fn add_vendor(mut self, id: u16, name: impl Into<String>) -> Self {
+        self.vendors
+            .push((Vendor::new(id, name.into()), Vec::new()));
+        self
+    }
+
+    /// Add a device to the vendor with the given id.
+    pub fn add_device_to(mut self, vendor_id: u16, id: u16, name: impl Into<String>) -> Self {
+        match self.vendors.iter_mut().find(|(v, _)| v.id() == vendor_id) {
+            Some((_, devices)) => devices.push((Device::new(id, name.into()), Vec::new())),
+            None => self.fail(BuildError::UnknownVendor { vendor_id }),
+        }
+        self
+    }
+
+    /// Add a subdevice to the given device of the vendor with the given id.
+    pub fn add_subdevice_to(
+        mut self,
+        vendor_id: u16,
+        device_id: u16,
+        subvendor_id: u16,
+        subdevice_id: u16,
+        name: impl Into<String>,
+    ) -> Self {
+        let device = self
+            .vendors
+            .iter_mut()
+            .find(|(v, _)| v.id() == vendor_id)
+            .and_then(|(_, devices)| devices.iter_mut().find(|(d, _)| d.id() == device_id));
+        match device {
+            Some((_, subdevices)) => {
+                subdevices.push(SubDevice::new(subvendor_id, subdevice_id, name.into()))
+            }
+            None => self.fail(BuildError::UnknownDevice {
+                vendor_id,
+                device_id,
+            }),
+        }
+        self
+    }
+
+    /// Add a class, named after its [DeviceClass](crate::device_class::DeviceClass).
+    pub fn add_class(mut self, id: u8) -> Self {
+        self.classes.push((Class::new(id), Vec::new()));
+        self
+    }
+
+    /// Add a subclass to the class with the given id.
+    pub fn add_subclass_to(mut self, class_id: u8, id: u8, name: impl Into<String>) -> Self {
+        match self.classes.iter_mut().find(|(c, _)| c.id() == class_id) {
+            Some((_, subclasses)) => subclasses.push((SubClass::new(id, name.into()), Vec::new())),
+            None => self.fail(BuildError::UnknownClass { class_id }),
+        }
+        self
+    }
+
+    /// Add a programming interface to the given subclass of the class with the given id.
+    pub fn add_interface_to(
+        mut self,
+        class_id: u8,
+        subclass_id: u8,
+        id: u8,
+        name: impl Into<String>,
+    ) -> Self {
+        let subclass = self
+            .classes
+            .iter_mut()
+            .find(|(c, _)| c.id() == class_id)
+            .and_then(|(_, subclasses)| subclasses.iter_mut().find(|(s, _)| s.id() == subclass_id));
+        match subclass {
+            Some((_, interfaces)) => interfaces.push(Interface::new(id, name.into())),
+            None => self.fail(BuildError::UnknownSubClass {
+                class_id,
+                subclass_id,
+            }),
+        }
+        self
+    }
+
+    /// Assemble the [PciIds].
+    ///
+    /// # Errors
+    /// Fails with the first [BuildError] encountered while adding entries.
+    pub fn build(self) -> Result<PciIds, BuildError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let vendors = self
+            .vendors
+            .into_iter()
+            .map(|(mut vendor, devices)| {
+                vendor.set_devices(
+                    devices
+                        .into_iter()
+                        .map(|(mut device, subdevices)| {
+                            device.set_subdevices(subdevices);
+                            device
+                        })
+                        .collect(),
+                );
+                vendor
+            })
+            .collect();
+        let classes = self
+            .classes
+            .into_iter()
+            .map(|(mut class, subclasses)| {
+                class.set_subclasses(
+                    subclasses
+                        .into_iter()
+                        .map(|(mut subclass, interfaces)| {
+                            subclass.set_interfaces(interfaces);
+                            subclass
+                        })
+                        .collect(),
+                );
+                class
+            })
+            .collect();
+
+        Ok(PciIds::from_parts(vendors, classes))
+    }
+
+    /// Remember the first error, later ones are usually caused by it.
+    fn fail(&mut self, error: BuildError) {
+        self.error.get_or_insert(error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::{BuildError, PciIdsBuilder};
+    use crate::device_class::DeviceClass;
+    use crate::ids::{SubDeviceId, VendorId};
+
+    /// Test that a built database is wired up and can be looked up like a parsed one
+    #[test]
+    fn test_build() {
+        let pci_ids = PciIdsBuilder::new()
+            .add_vendor(0x10de, "NVIDIA Corporation")
+            .add_vendor(0x1002, "Advanced Micro Devices, Inc. [AMD/ATI]")
+            .add_device_to(0x10de, 0x1b80, "GP104 [GeForce GTX 1080]")
+            .add_subdevice_to(0x10de, 0x1b80, 0x1043, 0x8591, "ROG STRIX GTX 1080")
+            .add_device_to(0x1002, 0x731f, "Navi 10")
+            .add_class(0x03)
+            .add_subclass_to(0x03, 0x00, "VGA compatible controller")
+            .add_interface_to(0x03, 0x00, 0x01, "8514 controller")
+            .build()
+            .unwrap();
+
+        assert_eq!(pci_ids.vendors().len(), 2);
+        let names = pci_ids.resolve(
+            0x10de,
+            0x1b80,
+            Some(VendorId(0x1043)),
+            Some(SubDeviceId(0x8591)),
+        );
+        assert_eq!(names.device, Some("GP104 [GeForce GTX 1080]"));
+        assert_eq!(names.subdevice, Some("ROG STRIX GTX 1080"));
+        let amd = pci_ids.vendor_by_id(0x1002).unwrap();
+        assert_eq!(amd.device_by_id(0x731f).unwrap().name(), "Navi 10");
+
+        assert_eq!(pci_ids.classes()[0].class(), DeviceClass::DisplayController);
+        let class = pci_ids.resolve_class(0x030001);
+        assert_eq!(class.subclass, Some("VGA compatible controller"));
+        assert_eq!(class.interface, Some("8514 controller"));
+    }
+
+    /// Test that adding to a missing parent fails with the first such error
+    #[test]
+    fn test_build_unknown_parent() {
+        let result = PciIdsBuilder::new()
+            .add_vendor(0x10de, "NVIDIA Corporation")
+            .add_subdevice_to(0x10de, 0x1b80, 0x1043, 0x8591, "ROG STRIX GTX 1080")
+            .add_device_to(0x1002, 0x731f, "Navi 10")
+            .build();
+        assert_eq!(
+            result,
+            Err(BuildError::UnknownDevice {
+                vendor_id: 0x10de,
+                device_id: 0x1b80
+            })
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "subdevice added to unknown device 10de:1b80"
+        );
+
+        let result = PciIdsBuilder::new()
+            .add_subclass_to(0x03, 0x00, "VGA")
+            .build();
+        assert_eq!(result, Err(BuildError::UnknownClass { class_id: 0x03 }));
+        let result = PciIdsBuilder::new()
+            .add_class(0x03)
+            .add_interface_to(0x03, 0x00, 0x01, "8514 controller")
+            .build();
+        assert_eq!(
+            result,
+            Err(BuildError::UnknownSubClass {
+                class_id: 0x03,
+                subclass_id: 0x00
+            })
+        );
+    }
+}
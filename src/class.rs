@@ -5,7 +5,14 @@
 //!
 //! ```
 
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
 use crate::device_class::DeviceClass;
+use crate::name::{Name, NamePool};
+use crate::pci_ids::ParseError;
 
 /// A category/class that a PCI device can belong to, along with eventual subclasses for more
 /// specificity.
@@ -16,6 +23,11 @@ use crate::device_class::DeviceClass;
 ///
 /// ```
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct Class {
     class: DeviceClass,
     subclasses: Vec<SubClass>,
@@ -23,15 +35,35 @@ pub struct Class {
 
 impl Class {
     /// Create a new class struct from a given id.
-    /// # Panics
-    /// Will panic upon receiving an invalid id that is not (yet) defined in [DeviceClass].
+    ///
+    /// An id that is not (yet) defined in [DeviceClass] becomes [DeviceClass::Other], use
+    /// [Class::try_new] to reject such ids instead.
     pub fn new(id: u8) -> Self {
         Self {
-            class: DeviceClass::try_from(id).unwrap(),
+            class: DeviceClass::from(id),
             subclasses: Vec::new(),
         }
     }
 
+    /// Create a new class struct from a given id, as long as the id is defined in [DeviceClass].
+    ///
+    /// # Errors
+    /// Fails with [ParseError::UnknownClass] for ids that would become [DeviceClass::Other].
+    pub fn try_new(id: u8) -> Result<Self, ParseError> {
+        match DeviceClass::from(id) {
+            DeviceClass::Other(_) => Err(ParseError::UnknownClass {
+                line_number: None,
+                id,
+            }),
+            _ => Ok(Self::new(id)),
+        }
+    }
+
+    /// Identifier of the class, the same as `u8::from(self.class())`.
+    pub fn id(&self) -> u8 {
+        u8::from(self.class)
+    }
+
     /// The [DeviceClass] a device can belong to.
     pub fn class(&self) -> DeviceClass {
         self.class
@@ -42,21 +74,105 @@ impl Class {
         &self.subclasses
     }
 
+    /// Number of subclasses of the class.
+    pub fn subclass_count(&self) -> usize {
+        self.subclasses.len()
+    }
+
+    /// Returns the subclass with the given id.
+    pub fn subclass_by_id(&self, id: u8) -> Option<&SubClass> {
+        self.subclasses.iter().find(|s| s.id == id)
+    }
+
     /// Set the subclasses to a given list of subclasses.
     pub(crate) fn set_subclasses(&mut self, subclasses: Vec<SubClass>) {
         self.subclasses = subclasses;
     }
+
+    /// Sort the subclasses and their interfaces by id, keeping entries with equal ids in order.
+    pub(crate) fn sort(&mut self) {
+        self.subclasses.sort_by_key(|s| s.id);
+        self.subclasses.iter_mut().for_each(SubClass::sort);
+    }
+
+    /// Heap memory owned by the class, its subclasses and their interfaces, in bytes.
+    pub(crate) fn heap_size(&self) -> usize {
+        self.subclasses.capacity() * core::mem::size_of::<SubClass>()
+            + self
+                .subclasses
+                .iter()
+                .map(SubClass::heap_size)
+                .sum::<usize>()
+    }
+
+    /// Replace the names of the subclasses and their interfaces by shared copies.
+    pub(crate) fn intern_names(&mut self, pool: &NamePool) {
+        self.subclasses
+            .iter_mut()
+            .for_each(|s| s.intern_names(pool));
+    }
+
+    /// Merge another class with the same id into `self`, merging or adding its subclasses.
+    pub(crate) fn merge(&mut self, other: Class) {
+        for subclass in other.subclasses {
+            match self.subclasses.iter_mut().find(|s| s.id == subclass.id) {
+                Some(s) => s.merge(subclass),
+                None => self.subclasses.push(subclass),
+            }
+        }
+    }
+}
+
+/// Iterate over the subclasses, in file order.
+impl<'a> IntoIterator for &'a Class {
+    type Item = &'a SubClass;
+    type IntoIter = core::slice::Iter<'a, SubClass>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.subclasses.iter()
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedClass {
+    /// Id of the class, like [Class::id].
+    pub fn id(&self) -> u8 {
+        self.class
+    }
+
+    /// The [DeviceClass] of the class, like [Class::class].
+    pub fn class(&self) -> DeviceClass {
+        DeviceClass::from(self.class)
+    }
+
+    /// Subclasses of the class, in file order.
+    pub fn subclasses(&self) -> &[ArchivedSubClass] {
+        &self.subclasses
+    }
+
+    /// Returns the first subclass with the given id, like [Class::subclass_by_id].
+    pub fn subclass_by_id(&self, id: u8) -> Option<&ArchivedSubClass> {
+        self.subclasses.iter().find(|s| s.id == id)
+    }
 }
 
 /// A subclass/subcategory of a type of PCI device.
 ///
 /// For example a 'network controller' can be everything from a fabric controller, an ethernet
 /// controller to an ATM controller.
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+///
+/// Subclasses are ordered by id, the remaining fields only break ties.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct SubClass {
     id: u8,
-    name: String,
+    name: Name,
     interfaces: Vec<Interface>,
+    comment: Option<String>,
 }
 
 impl SubClass {
@@ -64,8 +180,9 @@ impl SubClass {
     pub fn new(id: u8, name: String) -> Self {
         Self {
             id,
-            name,
+            name: name.into(),
             interfaces: Vec::new(),
+            comment: None,
         }
     }
 
@@ -74,6 +191,11 @@ impl SubClass {
         self.id
     }
 
+    /// Identifier of the subclass as 2 digit lowercase hex.
+    pub fn hex_id(&self) -> String {
+        format!("{:02x}", self.id)
+    }
+
     /// Name of the subclass.
     pub fn name(&self) -> &str {
         &self.name
@@ -84,24 +206,120 @@ impl SubClass {
         &self.interfaces
     }
 
+    /// Returns the programming interface with the given id.
+    pub fn interface_by_id(&self, id: u8) -> Option<&Interface> {
+        self.interfaces.iter().find(|i| i.id == id)
+    }
+
+    /// Comment attached to the subclass in the pci.ids file.
+    ///
+    /// # Note
+    /// Comments are only kept when parsing with
+    /// [keep_comments](crate::pci_ids::ParseOptions::keep_comments) set, which also describes what
+    /// they are made of.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Set the comment to a given text.
+    pub(crate) fn set_comment(&mut self, comment: Option<String>) {
+        self.comment = comment;
+    }
+
     /// Set the programming interfaces to a given list of interfaces.
     pub(crate) fn set_interfaces(&mut self, interfaces: Vec<Interface>) {
         self.interfaces = interfaces;
     }
+
+    /// Sort the interfaces by id, keeping entries with equal ids in order.
+    pub(crate) fn sort(&mut self) {
+        self.interfaces.sort_by_key(|i| i.id);
+    }
+
+    /// Heap memory owned by the subclass and its interfaces, in bytes.
+    pub(crate) fn heap_size(&self) -> usize {
+        self.name.heap_size()
+            + self.comment.as_ref().map_or(0, String::capacity)
+            + self.interfaces.capacity() * core::mem::size_of::<Interface>()
+            + self
+                .interfaces
+                .iter()
+                .map(Interface::heap_size)
+                .sum::<usize>()
+    }
+
+    /// Replace the names of the subclass and its interfaces by shared copies.
+    pub(crate) fn intern_names(&mut self, pool: &NamePool) {
+        pool.intern(&mut self.name);
+        self.interfaces
+            .iter_mut()
+            .for_each(|i| i.intern_names(pool));
+    }
+
+    /// Merge another subclass with the same id into `self`, taking its name and replacing or
+    /// adding its programming interfaces.
+    pub(crate) fn merge(&mut self, other: SubClass) {
+        self.name = other.name;
+        if other.comment.is_some() {
+            self.comment = other.comment;
+        }
+        for interface in other.interfaces {
+            match self.interfaces.iter_mut().find(|i| i.id == interface.id) {
+                Some(i) => *i = interface,
+                None => self.interfaces.push(interface),
+            }
+        }
+    }
+}
+
+impl fmt::Display for SubClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02x} {}", self.id, self.name)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedSubClass {
+    /// Id of the subclass, like [SubClass::id].
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// Name of the subclass, like [SubClass::name].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Programming interfaces of the subclass, in file order.
+    pub fn interfaces(&self) -> &[ArchivedInterface] {
+        &self.interfaces
+    }
 }
 
 /// A programming interface of a subclass, so yet a lower level of categorisation of a particular
 /// PCI device type.
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+///
+/// Interfaces are ordered by id, then name.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct Interface {
     id: u8,
-    name: String,
+    name: Name,
+    comment: Option<String>,
 }
 
 impl Interface {
     /// Create a new programming interface struct from a given id and name.
     pub fn new(id: u8, name: String) -> Self {
-        Self { id, name }
+        Self {
+            id,
+            name: name.into(),
+            comment: None,
+        }
     }
 
     /// Identifier of the programming interface.
@@ -109,9 +327,224 @@ impl Interface {
         self.id
     }
 
+    /// Identifier of the programming interface as 2 digit lowercase hex.
+    pub fn hex_id(&self) -> String {
+        format!("{:02x}", self.id)
+    }
+
     /// Name of the programming interface.
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Comment attached to the programming interface in the pci.ids file.
+    ///
+    /// # Note
+    /// Comments are only kept when parsing with
+    /// [keep_comments](crate::pci_ids::ParseOptions::keep_comments) set, which also describes what
+    /// they are made of.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Set the comment to a given text.
+    pub(crate) fn set_comment(&mut self, comment: Option<String>) {
+        self.comment = comment;
+    }
+
+    /// Heap memory owned by the programming interface, in bytes.
+    pub(crate) fn heap_size(&self) -> usize {
+        self.name.heap_size() + self.comment.as_ref().map_or(0, String::capacity)
+    }
+
+    /// Replace the name by a shared copy.
+    pub(crate) fn intern_names(&mut self, pool: &NamePool) {
+        pool.intern(&mut self.name);
+    }
+}
+
+impl fmt::Display for Interface {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02x} {}", self.id, self.name)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedInterface {
+    /// Id of the programming interface, like [Interface::id].
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// Name of the programming interface, like [Interface::name].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
 }
 
+/// Owned, display oriented view of a [Class] and everything below it, as produced by
+/// [PciIds::class_tree](crate::pci_ids::PciIds::class_tree).
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassNode {
+    /// Identifier of the class.
+    pub id: u8,
+    /// Name of the class.
+    pub name: String,
+    /// Subclasses of the class.
+    pub subclasses: Vec<SubClassNode>,
+}
+
+impl From<&Class> for ClassNode {
+    fn from(class: &Class) -> Self {
+        Self {
+            id: class.class.into(),
+            name: class.class.to_string(),
+            subclasses: class.subclasses.iter().map(SubClassNode::from).collect(),
+        }
+    }
+}
+
+/// Owned, display oriented view of a [SubClass] and its programming interfaces.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubClassNode {
+    /// Identifier of the subclass.
+    pub id: u8,
+    /// Name of the subclass.
+    pub name: String,
+    /// Programming interfaces of the subclass.
+    pub interfaces: Vec<InterfaceNode>,
+}
+
+impl From<&SubClass> for SubClassNode {
+    fn from(subclass: &SubClass) -> Self {
+        Self {
+            id: subclass.id,
+            name: subclass.name.to_string(),
+            interfaces: subclass
+                .interfaces
+                .iter()
+                .map(InterfaceNode::from)
+                .collect(),
+        }
+    }
+}
+
+/// Owned, display oriented view of an [Interface].
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InterfaceNode {
+    /// Identifier of the programming interface.
+    pub id: u8,
+    /// Name of the programming interface.
+    pub name: String,
+}
+
+impl From<&Interface> for InterfaceNode {
+    fn from(interface: &Interface) -> Self {
+        Self {
+            id: interface.id,
+            name: interface.name.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class::{Class, Interface, SubClass};
+    use crate::device_class::DeviceClass;
+    use crate::pci_ids::ParseError;
+    #[cfg(feature = "std")]
+    use crate::pci_ids::{PciIds, PATH_TO_PCI_IDS};
+    #[cfg(feature = "std")]
+    use std::path::Path;
+
+    /// Test that subclasses and interfaces sort by id
+    #[test]
+    fn test_ordering() {
+        let mut subclasses = [
+            SubClass::new(0x80, "Other".to_owned()),
+            SubClass::new(0x00, "SCSI".to_owned()),
+            SubClass::new(0x06, "SATA".to_owned()),
+        ];
+        subclasses.sort();
+        let ids: Vec<u8> = subclasses.iter().map(|s| s.id()).collect();
+        assert_eq!(ids, [0x00, 0x06, 0x80]);
+
+        let mut interfaces = [
+            Interface::new(0x30, "XHCI".to_owned()),
+            Interface::new(0x20, "EHCI".to_owned()),
+        ];
+        interfaces.sort();
+        assert_eq!(interfaces[0].name(), "EHCI");
+    }
+
+    /// Test that hex ids keep their leading zeros
+    #[test]
+    fn test_hex_id() {
+        assert_eq!(
+            SubClass::new(0x01, "IDE interface".to_owned()).hex_id(),
+            "01"
+        );
+        assert_eq!(Interface::new(0x8a, "ISA".to_owned()).hex_id(), "8a");
+    }
+
+    /// Test that ids are printed zero padded in front of the name
+    #[test]
+    fn test_display() {
+        let subclass = SubClass::new(0x01, "IDE interface".to_owned());
+        assert_eq!(subclass.to_string(), "01 IDE interface");
+        let interface = Interface::new(0x00, "ISA Compatibility mode-only controller".to_owned());
+        assert_eq!(
+            interface.to_string(),
+            "00 ISA Compatibility mode-only controller"
+        );
+    }
+
+    /// Test that only defined class ids make it through try_new
+    #[test]
+    fn test_try_new() {
+        assert_eq!(
+            Class::try_new(0x0c).unwrap().class(),
+            DeviceClass::SerialBusController
+        );
+        assert!(matches!(
+            Class::try_new(0x55),
+            Err(ParseError::UnknownClass {
+                line_number: None,
+                id: 0x55
+            })
+        ));
+        assert_eq!(Class::new(0x55).class(), DeviceClass::Other(0x55));
+    }
+
+    /// Test resolving the class code of an XHCI controller one level at a time
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_class_subclass_interface_by_id() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let class = pci_ids.class_by_id(0x0c).unwrap();
+        assert_eq!(class.class().to_string(), "Serial Bus Controller");
+        let subclass = class.subclass_by_id(0x03).unwrap();
+        assert_eq!(subclass.name(), "USB controller");
+        let interface = subclass.interface_by_id(0x30).unwrap();
+        assert_eq!(interface.name(), "XHCI");
+
+        assert!(pci_ids.class_by_id(0x14).is_none());
+        assert!(class.subclass_by_id(0x7f).is_none());
+        assert!(subclass.interface_by_id(0x7f).is_none());
+    }
+
+    /// Test that the id of every class in the file is its raw class byte
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_class_id() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        for class in pci_ids.classes() {
+            assert_eq!(class.id(), u8::from(class.class()));
+        }
+        assert_eq!(pci_ids.class_by_id(0x0c).unwrap().id(), 0x0c);
+        assert_eq!(Class::new(0x55).id(), 0x55);
+    }
+}
@@ -1,11 +1,19 @@
 //! The PCI ID repository specifies a bunch of classes and subclasses a device can be a part of.
 //! This module translates the hexadecimal value of a class or subclass to a more useful enum.
+//!
+//! Subclasses and programming interfaces only have their own enums for some classes so far, see
+//! [StorageInterface].
 
-use std::fmt;
+use core::fmt;
 
 /// The different classes a device can be apart of, as defined by: [https://pci-ids.ucw.cz/read/PD/](https://pci-ids.ucw.cz/read/PD/)
-// TODO: Make the subdevice classes and programming interfaces into their own enums
+// TODO: Make the remaining subdevice classes and programming interfaces into their own enums
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "u8", from = "u8")
+)]
 pub enum DeviceClass {
     /// 0x00
     Unclassified,
@@ -51,37 +59,38 @@ pub enum DeviceClass {
     Coprocessor,
     /// 0xff
     Unassigned,
+    /// Any class not (yet) known to this crate, holding its byte value.
+    Other(u8),
 }
 
-impl TryFrom<u8> for DeviceClass {
-    type Error = &'static str;
-    /// Retrieve the device class with the given byte. Will panic if the byte value does
-    /// not have a corresponding device class defined.
-    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+impl From<u8> for DeviceClass {
+    /// Retrieve the device class with the given byte. A byte value that does not have a
+    /// corresponding device class defined becomes [DeviceClass::Other].
+    fn from(byte: u8) -> Self {
         match byte {
-            0x00 => Ok(Self::Unclassified),
-            0x01 => Ok(Self::MassStorageController),
-            0x02 => Ok(Self::NetworkController),
-            0x03 => Ok(Self::DisplayController),
-            0x04 => Ok(Self::MultimediaController),
-            0x05 => Ok(Self::MemoryController),
-            0x06 => Ok(Self::Bridge),
-            0x07 => Ok(Self::CommunicationController),
-            0x08 => Ok(Self::GenericSystemPeripheral),
-            0x09 => Ok(Self::InputDeviceController),
-            0x0a => Ok(Self::DockingStation),
-            0x0b => Ok(Self::Processor),
-            0x0c => Ok(Self::SerialBusController),
-            0x0d => Ok(Self::WirelessController),
-            0x0e => Ok(Self::IntelligentController),
-            0x0f => Ok(Self::SatelliteCommunicationsController),
-            0x10 => Ok(Self::EncryptionController),
-            0x11 => Ok(Self::SignalProcessingController),
-            0x12 => Ok(Self::ProcessingAccelerator),
-            0x13 => Ok(Self::NonEssentialInstrumentation),
-            0x40 => Ok(Self::Coprocessor),
-            0xff => Ok(Self::Unassigned),
-            _ => Err("Invalid DeviceClass byte"),
+            0x00 => Self::Unclassified,
+            0x01 => Self::MassStorageController,
+            0x02 => Self::NetworkController,
+            0x03 => Self::DisplayController,
+            0x04 => Self::MultimediaController,
+            0x05 => Self::MemoryController,
+            0x06 => Self::Bridge,
+            0x07 => Self::CommunicationController,
+            0x08 => Self::GenericSystemPeripheral,
+            0x09 => Self::InputDeviceController,
+            0x0a => Self::DockingStation,
+            0x0b => Self::Processor,
+            0x0c => Self::SerialBusController,
+            0x0d => Self::WirelessController,
+            0x0e => Self::IntelligentController,
+            0x0f => Self::SatelliteCommunicationsController,
+            0x10 => Self::EncryptionController,
+            0x11 => Self::SignalProcessingController,
+            0x12 => Self::ProcessingAccelerator,
+            0x13 => Self::NonEssentialInstrumentation,
+            0x40 => Self::Coprocessor,
+            0xff => Self::Unassigned,
+            other => Self::Other(other),
         }
     }
 }
@@ -111,6 +120,66 @@ impl From<DeviceClass> for u8 {
             DeviceClass::NonEssentialInstrumentation => 0x13,
             DeviceClass::Coprocessor => 0x40,
             DeviceClass::Unassigned => 0xff,
+            DeviceClass::Other(byte) => byte,
+        }
+    }
+}
+
+/// Classes are archived as their byte, just like they are serialized.
+#[cfg(feature = "rkyv")]
+impl rkyv::Archive for DeviceClass {
+    type Archived = u8;
+    type Resolver = ();
+
+    fn resolve(&self, _: (), out: rkyv::Place<u8>) {
+        out.write(u8::from(*self));
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<S: rkyv::rancor::Fallible + ?Sized> rkyv::Serialize<S> for DeviceClass {
+    fn serialize(&self, _: &mut S) -> Result<(), S::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<D: rkyv::rancor::Fallible + ?Sized> rkyv::Deserialize<DeviceClass, D> for u8 {
+    fn deserialize(&self, _: &mut D) -> Result<DeviceClass, D::Error> {
+        Ok(DeviceClass::from(*self))
+    }
+}
+
+impl DeviceClass {
+    /// Human readable name of the class, as used by its [Display](fmt::Display) implementation.
+    ///
+    /// All classes that aren't known share the name `Unknown`, [Display](fmt::Display) adds their
+    /// byte value.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            DeviceClass::Unclassified => "Unclassified",
+            DeviceClass::MassStorageController => "Mass Storage Controller",
+            DeviceClass::NetworkController => "Network Controller",
+            DeviceClass::DisplayController => "Display Controller",
+            DeviceClass::MultimediaController => "Multimedia Controller",
+            DeviceClass::MemoryController => "Memory Controller",
+            DeviceClass::Bridge => "Bridge",
+            DeviceClass::CommunicationController => "Communication Controller",
+            DeviceClass::GenericSystemPeripheral => "Generic System Peripheral",
+            DeviceClass::InputDeviceController => "Input Device Controller",
+            DeviceClass::DockingStation => "Docking Station",
+            DeviceClass::Processor => "Processor",
+            DeviceClass::Coprocessor => "Coprocessor",
+            DeviceClass::SerialBusController => "Serial Bus Controller",
+            DeviceClass::WirelessController => "Wireless Controller",
+            DeviceClass::IntelligentController => "Intelligent Controller",
+            DeviceClass::SatelliteCommunicationsController => "Satellite Communications Controller",
+            DeviceClass::EncryptionController => "Encryption Controller",
+            DeviceClass::SignalProcessingController => "Signal Processing Controller",
+            DeviceClass::ProcessingAccelerator => "Processing Accelerators",
+            DeviceClass::NonEssentialInstrumentation => "Non Essential Instrumentation",
+            DeviceClass::Unassigned => "Unassigned",
+            DeviceClass::Other(_) => "Unknown",
         }
     }
 }
@@ -118,30 +187,157 @@ impl From<DeviceClass> for u8 {
 impl fmt::Display for DeviceClass {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            DeviceClass::Unclassified => write!(f, "Unclassified"),
-            DeviceClass::MassStorageController => write!(f, "Mass Storage Controller"),
-            DeviceClass::NetworkController => write!(f, "Network Controller"),
-            DeviceClass::DisplayController => write!(f, "Display Controller"),
-            DeviceClass::MultimediaController => write!(f, "Multimedia Controller"),
-            DeviceClass::MemoryController => write!(f, "Memory Controller"),
-            DeviceClass::Bridge => write!(f, "Bridge"),
-            DeviceClass::CommunicationController => write!(f, "Communication Controller"),
-            DeviceClass::GenericSystemPeripheral => write!(f, "Generic System Peripheral"),
-            DeviceClass::InputDeviceController => write!(f, "Input Device Controller"),
-            DeviceClass::DockingStation => write!(f, "Docking Station"),
-            DeviceClass::Processor => write!(f, "Processor"),
-            DeviceClass::Coprocessor => write!(f, "Coprocessor"),
-            DeviceClass::SerialBusController => write!(f, "Serial Bus Controller"),
-            DeviceClass::WirelessController => write!(f, "Wireless Controller"),
-            DeviceClass::IntelligentController => write!(f, "Intelligent Controller"),
-            DeviceClass::SatelliteCommunicationsController => {
-                write!(f, "Satellite Communications Controller")
-            }
-            DeviceClass::EncryptionController => write!(f, "Encryption Controller"),
-            DeviceClass::SignalProcessingController => write!(f, "Signal Processing Controller"),
-            DeviceClass::ProcessingAccelerator => write!(f, "Processing Accelerators"),
-            DeviceClass::NonEssentialInstrumentation => write!(f, "Non Essential Instrumentation"),
-            DeviceClass::Unassigned => write!(f, "Unassigned"),
+            DeviceClass::Other(byte) => write!(f, "{} ({byte:02x})", self.name()),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+/// Kinds of controllers in the [mass storage](DeviceClass::MassStorageController) class, told
+/// apart by their subclass and, where the subclass allows several, programming interface.
+///
+/// Only the common kinds are covered, anything else can still be looked up by name through
+/// [PciIds::resolve_class](crate::pci_ids::PciIds::resolve_class).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StorageInterface {
+    /// Subclass 0x00, any programming interface.
+    Scsi,
+    /// Subclass 0x01, any programming interface.
+    Ide,
+    /// Subclass 0x04, any programming interface.
+    Raid,
+    /// Subclass 0x06, programming interface 0x01.
+    Ahci,
+    /// Subclass 0x07, any programming interface.
+    Sas,
+    /// Subclass 0x08, programming interface 0x02.
+    Nvme,
+    /// Subclass 0x09, programming interface 0x01.
+    Ufs,
+}
+
+impl TryFrom<(u8, u8)> for StorageInterface {
+    /// The pair that doesn't match any of the kinds.
+    type Error = (u8, u8);
+
+    /// Find the kind of storage controller for a `(subclass, programming interface)` pair of the
+    /// mass storage class. The programming interface alone isn't enough, as e.g. `0x01` means
+    /// AHCI for SATA controllers but NVMHCI for non-volatile memory controllers.
+    fn try_from(codes: (u8, u8)) -> Result<Self, Self::Error> {
+        match codes {
+            (0x00, _) => Ok(Self::Scsi),
+            (0x01, _) => Ok(Self::Ide),
+            (0x04, _) => Ok(Self::Raid),
+            (0x06, 0x01) => Ok(Self::Ahci),
+            (0x07, _) => Ok(Self::Sas),
+            (0x08, 0x02) => Ok(Self::Nvme),
+            (0x09, 0x01) => Ok(Self::Ufs),
+            _ => Err(codes),
         }
     }
 }
+
+impl StorageInterface {
+    /// Find the kind of storage controller for a 24 bit class code like `0x010601`, `None` if
+    /// the code is outside of the mass storage class or not one of the kinds.
+    pub fn from_class_code(code: u32) -> Option<Self> {
+        let [_, class, subclass, interface] = code.to_be_bytes();
+        if DeviceClass::from(class) != DeviceClass::MassStorageController {
+            return None;
+        }
+        Self::try_from((subclass, interface)).ok()
+    }
+
+    /// Short name of the kind, as used by its [Display](fmt::Display) implementation.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            StorageInterface::Scsi => "SCSI",
+            StorageInterface::Ide => "IDE",
+            StorageInterface::Raid => "RAID",
+            StorageInterface::Ahci => "AHCI",
+            StorageInterface::Sas => "SAS",
+            StorageInterface::Nvme => "NVMe",
+            StorageInterface::Ufs => "UFS",
+        }
+    }
+}
+
+impl fmt::Display for StorageInterface {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::device_class::{DeviceClass, StorageInterface};
+    use crate::pci_ids::PATH_TO_PCI_IDS;
+
+    /// Test that every class in the real file is known and survives the round trip to its byte
+    #[test]
+    fn test_classes_of_real_file() {
+        let data = std::fs::read_to_string(PATH_TO_PCI_IDS).unwrap();
+        let bytes: Vec<u8> = data
+            .lines()
+            .filter_map(|line| line.strip_prefix("C "))
+            .map(|line| u8::from_str_radix(&line[..2], 16).unwrap())
+            .collect();
+        assert!(!bytes.is_empty());
+        for byte in bytes {
+            let class = DeviceClass::from(byte);
+            assert!(!matches!(class, DeviceClass::Other(_)), "class {byte:02x}");
+            assert_eq!(u8::from(class), byte);
+        }
+    }
+
+    /// Test that unknown classes keep their byte
+    #[test]
+    fn test_other_class() {
+        let class = DeviceClass::from(0x55);
+        assert_eq!(class, DeviceClass::Other(0x55));
+        assert_eq!(u8::from(class), 0x55);
+        assert_eq!(class.to_string(), "Unknown (55)");
+        assert_eq!(DeviceClass::from(0x0c).to_string(), "Serial Bus Controller");
+    }
+
+    /// Test that storage controllers are told apart by subclass and programming interface
+    #[test]
+    fn test_storage_interface() {
+        assert_eq!(
+            StorageInterface::try_from((0x06, 0x01)),
+            Ok(StorageInterface::Ahci)
+        );
+        assert_eq!(StorageInterface::Ahci.to_string(), "AHCI");
+        assert_eq!(StorageInterface::try_from((0x06, 0x02)), Err((0x06, 0x02)));
+        assert_eq!(
+            StorageInterface::try_from((0x01, 0x8a)),
+            Ok(StorageInterface::Ide)
+        );
+        assert_eq!(
+            StorageInterface::from_class_code(0x010802),
+            Some(StorageInterface::Nvme)
+        );
+        assert_eq!(StorageInterface::from_class_code(0x010801), None);
+        assert_eq!(StorageInterface::from_class_code(0x0c0601), None);
+    }
+
+    /// Test that the AHCI interface of the real file maps to its enum variant
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_storage_interface_of_real_file() {
+        use crate::pci_ids::PciIds;
+        use std::path::Path;
+
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let sata = pci_ids
+            .class_by_id(0x01)
+            .and_then(|c| c.subclass_by_id(0x06))
+            .unwrap();
+        let ahci = sata.interface_by_id(0x01).unwrap();
+        assert_eq!(ahci.name(), "AHCI 1.0");
+        assert_eq!(
+            StorageInterface::try_from((sata.id(), ahci.id())),
+            Ok(StorageInterface::Ahci)
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Comparison of two versions of the database, e.g. for a changelog of a new pci.ids release.
+//!
+//! ```
+//! use pci_id::pci_ids::PciIds;
+//!
+//! let old = PciIds::parse_from_str("1002  ATI Technologies Inc\n").unwrap();
+//! let new = PciIds::parse_from_str("1002  Advanced Micro Devices, Inc. [AMD/ATI]\n").unwrap();
+//! let diff = old.diff(&new);
+//! for rename in &diff.vendors.renamed {
+//!     println!("{:04x}: {} -> {}", rename.id, rename.old_name, rename.new_name);
+//! }
+//! assert_eq!(diff.vendors.renamed.len(), 1);
+//! assert!(old.diff(&old).is_empty());
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::pci_ids::PciIds;
+use crate::vendor::{Device, SubDevice, Vendor};
+
+/// Everything that differs between an old and a new [PciIds], as produced by [PciIds::diff].
+///
+/// Entries are identified by their ids and those of their parents, so a device is keyed by
+/// `(vendor, device)` and a subdevice by `(vendor, device, subvendor, subdevice)`, like in
+/// [IdTopology](crate::topology::IdTopology). If an id occurs more than once the first entry
+/// wins, just like with [PciIds::vendor_by_id].
+///
+/// The devices of an added or removed vendor are not listed again, and neither are the
+/// subdevices of an added or removed device.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct PciIdsDiff<'a> {
+    /// Changed vendors.
+    pub vendors: Changes<'a, u16, Vendor>,
+    /// Changed devices of the vendors in both databases.
+    pub devices: Changes<'a, (u16, u16), Device>,
+    /// Changed subdevices of the devices in both databases.
+    pub subdevices: Changes<'a, (u16, u16, u16, u16), SubDevice>,
+}
+
+/// The entries of one level of the hierarchy that were added, removed or renamed, each ordered
+/// by id.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Changes<'a, K, T> {
+    /// Entries only in the new database.
+    pub added: Vec<(K, &'a T)>,
+    /// Entries only in the old database.
+    pub removed: Vec<(K, &'a T)>,
+    /// Entries in both databases, but under a different name.
+    pub renamed: Vec<Rename<'a, K>>,
+}
+
+impl<K, T> Changes<'_, K, T> {
+    /// Whether nothing was added, removed or renamed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.renamed.is_empty()
+    }
+}
+
+impl<K, T> Default for Changes<'_, K, T> {
+    fn default() -> Self {
+        Self {
+            added: Vec::new(),
+            removed: Vec::new(),
+            renamed: Vec::new(),
+        }
+    }
+}
+
+/// An entry that kept its id but changed its name.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Rename<'a, K> {
+    /// Ids of the entry, keyed like in [Changes].
+    pub id: K,
+    /// Name in the old database.
+    pub old_name: &'a str,
+    /// Name in the new database.
+    pub new_name: &'a str,
+}
+
+impl<'a> PciIdsDiff<'a> {
+    /// Compare the `old` database to the `new` one.
+    pub fn new(old: &'a PciIds, new: &'a PciIds) -> Self {
+        let mut diff = Self::default();
+        let vendors = compare(
+            by_id(old.vendors().iter().map(|v| (v.id(), v))),
+            by_id(new.vendors().iter().map(|v| (v.id(), v))),
+            Vendor::name,
+            &mut diff.vendors,
+        );
+        for (vendor_id, old, new) in vendors {
+            let devices = compare(
+                by_id(old.devices().iter().map(|d| ((vendor_id, d.id()), d))),
+                by_id(new.devices().iter().map(|d| ((vendor_id, d.id()), d))),
+                Device::name,
+                &mut diff.devices,
+            );
+            for ((vendor_id, device_id), old, new) in devices {
+                let key =
+                    |s: &SubDevice| (vendor_id, device_id, s.subvendor_id(), s.subdevice_id());
+                compare(
+                    by_id(old.subdevices().iter().map(|s| (key(s), s))),
+                    by_id(new.subdevices().iter().map(|s| (key(s), s))),
+                    SubDevice::name,
+                    &mut diff.subdevices,
+                );
+            }
+        }
+        diff
+    }
+
+    /// Whether both databases hold the same vendors, devices and subdevices under the same names.
+    pub fn is_empty(&self) -> bool {
+        self.vendors.is_empty() && self.devices.is_empty() && self.subdevices.is_empty()
+    }
+}
+
+/// Key the entries by their ids, keeping the first entry of every id.
+fn by_id<'a, K: Ord, T>(entries: impl Iterator<Item = (K, &'a T)>) -> BTreeMap<K, &'a T> {
+    let mut map = BTreeMap::new();
+    for (id, entry) in entries {
+        map.entry(id).or_insert(entry);
+    }
+    map
+}
+
+/// Record the entries that were added, removed or renamed in `changes`, and return the pairs of
+/// entries in both, for comparing the level below.
+fn compare<'a, K: Ord + Copy, T>(
+    old: BTreeMap<K, &'a T>,
+    mut new: BTreeMap<K, &'a T>,
+    name: fn(&T) -> &str,
+    changes: &mut Changes<'a, K, T>,
+) -> Vec<(K, &'a T, &'a T)> {
+    let mut common = Vec::new();
+    for (id, old) in old {
+        match new.remove(&id) {
+            Some(new) => {
+                if name(old) != name(new) {
+                    changes.renamed.push(Rename {
+                        id,
+                        old_name: name(old),
+                        new_name: name(new),
+                    });
+                }
+                common.push((id, old, new));
+            }
+            None => changes.removed.push((id, old)),
+        }
+    }
+    changes.added.extend(new);
+    common
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diff::Rename;
+    use crate::pci_ids::PciIds;
+
+    /// Test that additions, removals and renames are told apart on every level
+    #[test]
+    fn test_diff() {
+        let old = PciIds::parse_from_str(
+            "0e11  Compaq Computer Corporation\n\
+             \t0046  Smart Array 64xx\n\
+             \t\t0e11 409a  Smart Array 641\n\
+             \t\t0e11 409b  Smart Array 642\n\
+             10de  NVIDIA Corporation\n\
+             \t1b80  GP104 [GeForce GTX 1080]\n\
+             1af4  Red Hat, Inc.\n",
+        )
+        .unwrap();
+        let new = PciIds::parse_from_str(
+            "0e11  Compaq Computer Corporation\n\
+             \t0046  Smart Array 64xx\n\
+             \t\t0e11 409a  Smart Array 641\n\
+             \t\t0e11 409b  Smart Array 642 Controller\n\
+             10de  NVIDIA Corporation\n\
+             \t1b80  GP104 [GeForce GTX 1080]\n\
+             \t1b81  GP104 [GeForce GTX 1070]\n\
+             1002  Advanced Micro Devices, Inc. [AMD/ATI]\n\
+             \t6798  Tahiti XT\n",
+        )
+        .unwrap();
+
+        let diff = old.diff(&new);
+        assert!(!diff.is_empty());
+        let added: Vec<u16> = diff.vendors.added.iter().map(|(id, _)| *id).collect();
+        let removed: Vec<u16> = diff.vendors.removed.iter().map(|(id, _)| *id).collect();
+        assert_eq!(added, [0x1002]);
+        assert_eq!(removed, [0x1af4]);
+        assert!(diff.vendors.renamed.is_empty());
+
+        assert_eq!(diff.devices.added.len(), 1);
+        assert_eq!(diff.devices.added[0].0, (0x10de, 0x1b81));
+        assert_eq!(diff.devices.added[0].1.name(), "GP104 [GeForce GTX 1070]");
+        assert!(diff.devices.removed.is_empty());
+        assert!(diff.devices.renamed.is_empty());
+
+        assert!(diff.subdevices.added.is_empty());
+        assert!(diff.subdevices.removed.is_empty());
+        assert_eq!(
+            diff.subdevices.renamed,
+            [Rename {
+                id: (0x0e11, 0x0046, 0x0e11, 0x409b),
+                old_name: "Smart Array 642",
+                new_name: "Smart Array 642 Controller",
+            }]
+        );
+
+        let reverse = new.diff(&old);
+        assert_eq!(reverse.vendors.added, diff.vendors.removed);
+        assert_eq!(reverse.devices.removed, diff.devices.added);
+        assert_eq!(reverse.subdevices.renamed[0].new_name, "Smart Array 642");
+        assert!(old.diff(&old).is_empty());
+    }
+}
@@ -0,0 +1,194 @@
+//! Distinct types for the different kinds of ids, so a vendor id can't be passed where a device id
+//! is expected.
+//!
+//! Lookups accept either the typed ids or plain integers, except for the subsystem ids of
+//! [PciIds::resolve](crate::pci_ids::PciIds::resolve), which are the pair most easily swapped:
+//! ```
+//! use pci_id::ids::{DeviceId, SubDeviceId, VendorId};
+//! use pci_id::pci_ids::PciIds;
+//!
+//! let pci_ids = PciIds::parse_from_str("0e11  Compaq\n\t0046  Smart Array 64xx\n").unwrap();
+//! let typed = pci_ids.resolve(VendorId(0x0e11), DeviceId(0x0046), None, None);
+//! assert_eq!(typed, pci_ids.resolve(0x0e11, 0x0046, None, None));
+//! pci_ids.resolve(0x0e11, 0x0046, Some(VendorId(0x0e11)), Some(SubDeviceId(0x409a)));
+//! ```
+//!
+//! Mixing up typed ids doesn't compile:
+//! ```compile_fail
+//! use pci_id::ids::{DeviceId, VendorId};
+//! use pci_id::pci_ids::PciIds;
+//!
+//! let pci_ids = PciIds::parse_from_str("0e11  Compaq\n\t0046  Smart Array 64xx\n").unwrap();
+//! pci_ids.resolve(DeviceId(0x0046), VendorId(0x0e11), None, None);
+//! ```
+//!
+//! and neither does swapping the subsystem ids:
+//! ```compile_fail
+//! use pci_id::ids::{SubDeviceId, VendorId};
+//! use pci_id::pci_ids::PciIds;
+//!
+//! let pci_ids = PciIds::parse_from_str("0e11  Compaq\n\t0046  Smart Array 64xx\n").unwrap();
+//! pci_ids.resolve(0x0e11, 0x0046, Some(SubDeviceId(0x409a)), Some(VendorId(0x0e11)));
+//! ```
+
+use core::fmt;
+
+/// Id of a vendor, or of a subvendor.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct VendorId(pub u16);
+
+/// Id of a device within its vendor.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct DeviceId(pub u16);
+
+/// Id of a subdevice within its subvendor.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct SubDeviceId(pub u16);
+
+/// Id of a class, the first byte of a class code.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct ClassId(pub u8);
+
+impl From<u16> for VendorId {
+    fn from(id: u16) -> Self {
+        Self(id)
+    }
+}
+
+impl From<VendorId> for u16 {
+    fn from(id: VendorId) -> u16 {
+        id.0
+    }
+}
+
+impl From<u16> for DeviceId {
+    fn from(id: u16) -> Self {
+        Self(id)
+    }
+}
+
+impl From<DeviceId> for u16 {
+    fn from(id: DeviceId) -> u16 {
+        id.0
+    }
+}
+
+impl From<u16> for SubDeviceId {
+    fn from(id: u16) -> Self {
+        Self(id)
+    }
+}
+
+impl From<SubDeviceId> for u16 {
+    fn from(id: SubDeviceId) -> u16 {
+        id.0
+    }
+}
+
+impl From<u8> for ClassId {
+    fn from(id: u8) -> Self {
+        Self(id)
+    }
+}
+
+impl From<ClassId> for u8 {
+    fn from(id: ClassId) -> u8 {
+        id.0
+    }
+}
+
+impl fmt::Display for VendorId {
+    /// Four lowercase hex digits, like in pci.ids.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04x}", self.0)
+    }
+}
+
+impl fmt::Display for DeviceId {
+    /// Four lowercase hex digits, like in pci.ids.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04x}", self.0)
+    }
+}
+
+impl fmt::Display for SubDeviceId {
+    /// Four lowercase hex digits, like in pci.ids.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04x}", self.0)
+    }
+}
+
+impl fmt::Display for ClassId {
+    /// Two lowercase hex digits, like in pci.ids.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02x}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ids::{ClassId, DeviceId, SubDeviceId, VendorId};
+    #[cfg(feature = "std")]
+    use crate::pci_ids::{PciIds, PATH_TO_PCI_IDS};
+    #[cfg(feature = "std")]
+    use std::path::Path;
+
+    /// Test converting and printing the ids
+    #[test]
+    fn test_ids() {
+        assert_eq!(VendorId::from(0x10de), VendorId(0x10de));
+        assert_eq!(u16::from(DeviceId(0x1b80)), 0x1b80);
+        assert_eq!(u8::from(ClassId::from(0x0c)), 0x0c);
+        assert_eq!(VendorId(0x0e11).to_string(), "0e11");
+        assert_eq!(DeviceId(0x46).to_string(), "0046");
+        assert_eq!(u16::from(SubDeviceId::from(0x409a)), 0x409a);
+        assert_eq!(SubDeviceId(0x9a).to_string(), "009a");
+        assert_eq!(ClassId(0x3).to_string(), "03");
+    }
+
+    /// Test that lookups take typed ids just like plain integers
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_typed_lookups() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let vendor = VendorId(0x0e11);
+        let device = DeviceId(0x0046);
+
+        let compaq = pci_ids.vendor_by_id(vendor).unwrap();
+        assert_eq!(
+            compaq.device_by_id(device).unwrap().name(),
+            "Smart Array 64xx"
+        );
+        assert_eq!(pci_ids.vendor_by_id_sorted(vendor), Some(compaq));
+        assert_eq!(
+            pci_ids.resolve(vendor, device, Some(vendor), Some(SubDeviceId(0x409a))),
+            pci_ids.resolve(
+                0x0e11,
+                0x0046,
+                Some(VendorId(0x0e11)),
+                Some(SubDeviceId(0x409a))
+            )
+        );
+        assert_eq!(pci_ids.class_by_id(ClassId(0x0c)).unwrap().id(), 0x0c);
+    }
+}
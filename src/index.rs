@@ -0,0 +1,166 @@
+//! Constant time lookups of vendors, devices and subdevices by id, and of vendors by name.
+
+use std::collections::HashMap;
+
+use crate::pci_ids::PciIds;
+use crate::vendor::{Device, SubDevice, Vendor};
+
+/// Hash map based index over a [PciIds], for resolving many ids without scanning the lists.
+///
+/// The index only holds references into the database, so no names are duplicated. If an id
+/// occurs more than once the first entry wins, just like with [PciIds::vendor_by_id].
+/// Created with [PciIds::index].
+#[derive(Debug, Clone)]
+pub struct IndexedPciIds<'a> {
+    pci_ids: &'a PciIds,
+    vendors: HashMap<u16, VendorIndex<'a>>,
+    vendors_by_name: HashMap<&'a str, &'a Vendor>,
+}
+
+#[derive(Debug, Clone)]
+struct VendorIndex<'a> {
+    vendor: &'a Vendor,
+    devices: HashMap<u16, DeviceIndex<'a>>,
+}
+
+#[derive(Debug, Clone)]
+struct DeviceIndex<'a> {
+    device: &'a Device,
+    subdevices: HashMap<(u16, u16), &'a SubDevice>,
+}
+
+impl<'a> IndexedPciIds<'a> {
+    /// Build the index over every vendor, device and subdevice of the given database.
+    pub fn new(pci_ids: &'a PciIds) -> Self {
+        let mut vendors = HashMap::with_capacity(pci_ids.vendors().len());
+        let mut vendors_by_name = HashMap::with_capacity(pci_ids.vendors().len());
+        for vendor in pci_ids.vendors() {
+            vendors_by_name.entry(vendor.name()).or_insert(vendor);
+            vendors.entry(vendor.id()).or_insert_with(|| {
+                let mut devices = HashMap::with_capacity(vendor.devices().len());
+                for device in vendor.devices() {
+                    devices.entry(device.id()).or_insert_with(|| {
+                        let mut subdevices = HashMap::with_capacity(device.subdevices().len());
+                        for subdevice in device.subdevices() {
+                            subdevices
+                                .entry((subdevice.subvendor_id(), subdevice.subdevice_id()))
+                                .or_insert(subdevice);
+                        }
+                        DeviceIndex { device, subdevices }
+                    });
+                }
+                VendorIndex { vendor, devices }
+            });
+        }
+        Self {
+            pci_ids,
+            vendors,
+            vendors_by_name,
+        }
+    }
+
+    /// The database the index was built from.
+    pub fn pci_ids(&self) -> &'a PciIds {
+        self.pci_ids
+    }
+
+    /// Look up the vendor with the given id.
+    pub fn get_vendor(&self, vendor: u16) -> Option<&'a Vendor> {
+        self.vendors.get(&vendor).map(|v| v.vendor)
+    }
+
+    /// Look up the vendor with exactly the given name, case and all.
+    ///
+    /// For a case insensitive substring search use [PciIds::find_vendors_by_name].
+    pub fn get_vendor_by_name(&self, name: &str) -> Option<&'a Vendor> {
+        self.vendors_by_name.get(name).copied()
+    }
+
+    /// Look up the given vendor's device.
+    pub fn get_device(&self, vendor: u16, device: u16) -> Option<&'a Device> {
+        self.vendors
+            .get(&vendor)?
+            .devices
+            .get(&device)
+            .map(|d| d.device)
+    }
+
+    /// Look up the subdevice with the given subvendor and subdevice ids of the given device.
+    pub fn get_subdevice(
+        &self,
+        vendor: u16,
+        device: u16,
+        subvendor: u16,
+        subdevice: u16,
+    ) -> Option<&'a SubDevice> {
+        self.vendors
+            .get(&vendor)?
+            .devices
+            .get(&device)?
+            .subdevices
+            .get(&(subvendor, subdevice))
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pci_ids::{PciIds, PATH_TO_PCI_IDS};
+    use std::path::Path;
+
+    /// Test that indexed lookups agree with linear ones for every entry and for misses
+    #[test]
+    fn test_index_agrees_with_linear_lookup() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let index = pci_ids.index();
+        for vendor in pci_ids.vendors() {
+            let linear = pci_ids.vendor_by_id(vendor.id());
+            assert_eq!(index.get_vendor(vendor.id()), linear);
+            for device in vendor.devices() {
+                let linear = linear.and_then(|v| v.device_by_id(device.id()));
+                assert_eq!(index.get_device(vendor.id(), device.id()), linear);
+                for subdevice in device.subdevices() {
+                    let (subvendor_id, subdevice_id) =
+                        (subdevice.subvendor_id(), subdevice.subdevice_id());
+                    assert_eq!(
+                        index.get_subdevice(vendor.id(), device.id(), subvendor_id, subdevice_id),
+                        linear.and_then(|d| d.subdevice_by_ids(subvendor_id, subdevice_id))
+                    );
+                }
+            }
+        }
+
+        assert!(index.get_vendor(0x0abc).is_none());
+        assert!(index.get_device(0x0e11, 0xfffe).is_none());
+        assert!(index
+            .get_subdevice(0x0e11, 0x0046, 0x409a, 0x0e11)
+            .is_none());
+        assert_eq!(
+            index
+                .get_subdevice(0x0e11, 0x0046, 0x0e11, 0x409a)
+                .map(|s| s.name()),
+            Some("Smart Array 641")
+        );
+    }
+
+    /// Test looking up vendors by their exact name
+    #[test]
+    fn test_get_vendor_by_name() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let index = pci_ids.index();
+        let amd = index
+            .get_vendor_by_name("Advanced Micro Devices, Inc. [AMD]")
+            .unwrap();
+        assert_eq!(amd.id(), 0x1022);
+        assert!(index
+            .get_vendor_by_name("Advanced Micro Devices, Inc. [AMD")
+            .is_none());
+        assert!(index
+            .get_vendor_by_name("advanced micro devices, inc. [amd]")
+            .is_none());
+        for vendor in pci_ids.vendors() {
+            let first = pci_ids.vendors().iter().find(|v| v.name() == vendor.name());
+            assert_eq!(index.get_vendor_by_name(vendor.name()), first);
+        }
+    }
+}
@@ -1,8 +1,10 @@
 //! ```
+//! # #[cfg(feature = "std")] {
 //! use std::path::Path;
-//! use pci_id::{PciIds, PATH_TO_PCI_IDS, Device};
+//! use pci_id::pci_ids::{PciIds, PATH_TO_PCI_IDS};
+//! use pci_id::vendor::Device;
 //!
-//! let pci_ids = PciIds::parse_pci_id_list(Path::new(DEFAULT_PATH_TO_PCI_IDS)).unwrap();
+//! let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
 //! let amd_devices = pci_ids.vendors().iter().find(|v| v.name() == "Advanced Micro Devices, Inc. [AMD/ATI]").unwrap();
 //! let navi_10: Vec<&Device> = amd_devices.devices().iter().filter(|d| d.name() == "Navi 10 [Radeon RX 5600 OEM/5600 XT / 5700/5700 XT]").collect();
 //! for device in navi_10 {
@@ -10,13 +12,46 @@
 //!        println!("{}", subdevice.name())
 //!     }
 //! }
+//! # }
 //! ```
-#![forbid(unsafe_code)]
+//!
+//! # no_std
+//! Without the default `std` feature, but with `alloc`, the crate works on targets that only
+//! have an allocator. Files then have to be loaded some other way and passed to
+//! `PciIds::parse_from_str`, while reading from paths or readers, the `index` and `resolver`
+//! modules and the features building on them are unavailable.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![cfg_attr(not(feature = "mmap"), forbid(unsafe_code))]
+#![cfg_attr(feature = "mmap", deny(unsafe_code))]
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 
+#[cfg(not(feature = "alloc"))]
+compile_error!("pci-id needs at least the `alloc` feature");
+
+extern crate alloc;
+
+pub mod address;
+pub mod borrowed;
+pub mod builder;
 pub mod device_class;
+pub mod diff;
+pub mod ids;
+#[cfg(feature = "std")]
+pub mod index;
+mod name;
 pub mod pci_ids;
+#[cfg(feature = "std")]
+pub mod resolver;
+#[cfg(feature = "sysfs")]
+pub mod sysfs;
+pub mod topology;
+#[cfg(feature = "online")]
+pub mod update;
 pub mod vendor;
+pub mod visitor;
 pub mod class;
 
+#[cfg(feature = "std")]
+pub use pci_ids::find_pci_ids_path;
+
@@ -0,0 +1,206 @@
+//! Storage for the names of entries, which can be shared between entries once interned.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+use core::{fmt, mem};
+
+/// Bytes an [Arc] allocates in front of the data, for the strong and weak counts.
+const ARC_HEADER: usize = 2 * mem::size_of::<usize>();
+
+/// Name of a vendor, device, subdevice, subclass or interface.
+///
+/// Behaves like the [str] it holds, no matter whether it is owned or shared, so interning never
+/// changes how entries compare, hash, print or serialize.
+#[derive(Clone)]
+pub(crate) enum Name {
+    Owned(String),
+    Shared(Arc<str>),
+}
+
+impl Name {
+    /// The name as a string slice.
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Self::Owned(name) => name,
+            Self::Shared(name) => name,
+        }
+    }
+
+    /// Heap memory attributed to this name, in bytes.
+    ///
+    /// A shared allocation is split evenly between everything sharing it, so that summing over all
+    /// entries roughly gives the memory actually in use.
+    pub(crate) fn heap_size(&self) -> usize {
+        match self {
+            Self::Owned(name) => name.capacity(),
+            Self::Shared(name) => (name.len() + ARC_HEADER) / Arc::strong_count(name),
+        }
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Self::Owned(name)
+    }
+}
+
+impl Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Name {}
+
+impl PartialOrd for Name {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Name {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for Name {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl fmt::Debug for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Name {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Name {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::Owned)
+    }
+}
+
+/// Names are archived as plain strings, so archives don't depend on whether names were interned.
+#[cfg(feature = "rkyv")]
+impl rkyv::Archive for Name {
+    type Archived = rkyv::string::ArchivedString;
+    type Resolver = rkyv::string::StringResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: rkyv::Place<Self::Archived>) {
+        rkyv::string::ArchivedString::resolve_from_str(self.as_str(), resolver, out);
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<S> rkyv::Serialize<S> for Name
+where
+    S: rkyv::rancor::Fallible + rkyv::ser::Writer + ?Sized,
+    S::Error: rkyv::rancor::Source,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        rkyv::string::ArchivedString::serialize_from_str(self.as_str(), serializer)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<D: rkyv::rancor::Fallible + ?Sized> rkyv::Deserialize<Name, D>
+    for rkyv::string::ArchivedString
+{
+    fn deserialize(&self, _: &mut D) -> Result<Name, D::Error> {
+        Ok(Name::Owned(self.as_str().into()))
+    }
+}
+
+/// The names worth sharing, each stored a single time.
+#[derive(Debug, Default)]
+pub(crate) struct NamePool {
+    shared: BTreeSet<Arc<str>>,
+}
+
+impl NamePool {
+    /// Create a pool of the names that occur often enough among the given ones for sharing them to
+    /// take less memory than the copies do, after paying for the reference counts.
+    pub(crate) fn for_repeated<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for name in names {
+            *counts.entry(name).or_default() += 1;
+        }
+        let shared = counts
+            .into_iter()
+            .filter(|&(name, count)| (count - 1) * name.len() > ARC_HEADER)
+            .map(|(name, _)| Arc::from(name))
+            .collect();
+        Self { shared }
+    }
+
+    /// Replace the given name by its shared copy, if there is one.
+    pub(crate) fn intern(&self, name: &mut Name) {
+        if let Name::Owned(owned) = name {
+            if let Some(shared) = self.shared.get(owned.as_str()) {
+                *name = Name::Shared(Arc::clone(shared));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::Arc;
+
+    use crate::name::{Name, NamePool};
+
+    /// Test that only names repeated often enough are shared and that sharing is invisible to
+    /// comparisons and formatting
+    #[test]
+    fn test_name_pool() {
+        let intel = "Intel Corporation";
+        let pool = NamePool::for_repeated([intel, "AMD", intel, "AMD", "AMD"]);
+        let mut first = Name::from(intel.to_owned());
+        let mut second = Name::from(intel.to_owned());
+        let mut short = Name::from("AMD".to_owned());
+        let before = first.heap_size() + second.heap_size();
+        pool.intern(&mut first);
+        pool.intern(&mut second);
+        pool.intern(&mut short);
+        drop(pool);
+
+        assert!(
+            matches!((&first, &second), (Name::Shared(a), Name::Shared(b)) if Arc::ptr_eq(a, b))
+        );
+        assert!(matches!(short, Name::Owned(_)));
+        assert!(first.heap_size() + second.heap_size() < before);
+        assert_eq!(first, Name::from(intel.to_owned()));
+        assert_eq!(
+            format!("{first:?} {second}"),
+            "\"Intel Corporation\" Intel Corporation"
+        );
+    }
+}
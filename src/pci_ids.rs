@@ -10,11 +10,44 @@
 
 // TODO: Replace manual parsing with either `nom` or `pest` if performance is better.
 
-use std::num::ParseIntError;
-use std::{io, path::Path};
+use alloc::borrow::{Cow, ToOwned};
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::num::ParseIntError;
+use core::ops::ControlFlow;
+use core::str::Utf8Error;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead};
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "online")]
+use std::time::Duration;
 
-use crate::class::{Class, SubClass, Interface};
+#[cfg(feature = "rkyv")]
+use crate::class::ArchivedClass;
+use crate::class::{Class, ClassNode, Interface, SubClass};
+use crate::diff::PciIdsDiff;
+use crate::ids::{ClassId, DeviceId, SubDeviceId, VendorId};
+#[cfg(feature = "std")]
+use crate::index::IndexedPciIds;
+use crate::name::NamePool;
+#[cfg(feature = "std")]
+use crate::resolver::CachedResolver;
+#[cfg(feature = "sysfs")]
+use crate::sysfs::{self, SystemDevice};
+use crate::topology::IdTopology;
+#[cfg(feature = "online")]
+use crate::update::UpdateError;
+#[cfg(feature = "rkyv")]
+use crate::vendor::ArchivedVendor;
 use crate::vendor::{Vendor, Device, SubDevice};
+use crate::visitor::PciIdsVisitor;
 
 /// Default path for the pci.ids file.
 ///
@@ -22,11 +55,401 @@ use crate::vendor::{Vendor, Device, SubDevice};
 /// If this differs from your system you can supply your own path to the functions that require one.
 pub const PATH_TO_PCI_IDS: &str = "/usr/share/hwdata/pci.ids";
 
+/// Environment variable that [PciIds::from_default_path] takes the path of the pci.ids file from.
+pub const PCI_IDS_PATH_VAR: &str = "PCI_IDS_PATH";
+
+/// Places distributions commonly install the pci.ids file to, in the order
+/// [PciIds::from_default_path] tries them.
+pub const KNOWN_PATHS: &[&str] = &[PATH_TO_PCI_IDS, "/usr/share/misc/pci.ids"];
+
+/// Snapshot of the pci.ids file embedded with the `bundled` feature.
+#[cfg(feature = "bundled")]
+const BUNDLED_PCI_IDS: &str = include_str!("../data/pci.ids");
+
+/// Number of lines between two calls of the callback given to [PciIds::parse_with_progress].
+const PROGRESS_INTERVAL: usize = 4096;
+
+/// Errors that can occur while parsing a pci.ids file.
+///
+/// Some variants only exist with the `std` feature, so matching on it needs a wildcard arm.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// Reading the file(s) failed.
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    /// None of the places a pci.ids file was looked for holds one.
+    #[cfg(feature = "std")]
+    NotFound {
+        /// Every path that was tried, in order.
+        tried: Vec<PathBuf>,
+    },
+    /// An id in the file is not a valid hexadecimal number.
+    InvalidHex {
+        /// Line in the file, starting at 1.
+        line_number: usize,
+        /// Why the id could not be parsed.
+        source: ParseIntError,
+    },
+    /// An id given as input is not a valid hexadecimal number.
+    InvalidId {
+        /// The offending input.
+        id: String,
+        /// Why it could not be parsed.
+        source: ParseIntError,
+    },
+    /// A `vendor:device` selector does not have the expected shape.
+    MalformedSelector {
+        /// The offending input.
+        selector: String,
+    },
+    /// A [SubsystemId](crate::vendor::SubsystemId) does not have the `subvendor:subdevice` shape.
+    MalformedSubsystem {
+        /// The offending input.
+        subsystem: String,
+    },
+    /// A PCI address does not have the `domain:bus:device.function` shape, or a part of it is
+    /// out of range.
+    MalformedAddress {
+        /// The offending input.
+        address: String,
+    },
+    /// A class id is not defined in [DeviceClass](crate::device_class::DeviceClass).
+    UnknownClass {
+        /// Line in the file, starting at 1, or `None` if the id did not come from a file.
+        line_number: Option<usize>,
+        /// The unknown class id.
+        id: u8,
+    },
+    /// A subdevice line does not have a device line above it within the same vendor.
+    OrphanSubdevice {
+        /// Line in the file, starting at 1.
+        line_number: usize,
+    },
+    /// A line lacks the separator between its id and name, or between the two ids of a subdevice.
+    MissingSeparator {
+        /// Line in the file, starting at 1.
+        line_number: usize,
+    },
+    /// A line is indented with spaces, or its indentation does not fit the entry above it.
+    UnexpectedIndent {
+        /// Line in the file, starting at 1.
+        line_number: usize,
+    },
+    /// A line is indented deeper than the two levels the format allows.
+    TooDeeplyNested {
+        /// Line in the file, starting at 1.
+        line_number: usize,
+        /// Number of leading tabs on the line.
+        depth: usize,
+    },
+    /// The file is not valid UTF-8, and [ParseOptions::lossy_utf8] is turned off.
+    InvalidUtf8 {
+        /// Line in the file, starting at 1.
+        line_number: usize,
+        /// Offset of the first invalid byte from the start of the file.
+        byte_offset: usize,
+        /// Why the bytes could not be decoded.
+        source: Utf8Error,
+    },
+}
+
+impl ParseError {
+    /// Whether the error is about the indentation of a line.
+    fn is_indentation(&self) -> bool {
+        matches!(
+            self,
+            Self::UnexpectedIndent { .. } | Self::TooDeeplyNested { .. }
+        )
+    }
+
+    /// Locate the first invalid byte of `data`.
+    fn invalid_utf8(data: &[u8], source: Utf8Error) -> Self {
+        let byte_offset = source.valid_up_to();
+        let line_number = data[..byte_offset].iter().filter(|&&b| b == b'\n').count() + 1;
+        Self::InvalidUtf8 {
+            line_number,
+            byte_offset,
+            source,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    /// Describe the error, starting with the line it is about if it came from a file. Underlying
+    /// errors are left to [source](core::error::Error::source).
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(_) => f.write_str("failed to read the pci.ids file"),
+            #[cfg(feature = "std")]
+            Self::NotFound { tried } => {
+                f.write_str("no pci.ids file found, tried")?;
+                for (index, path) in tried.iter().enumerate() {
+                    let separator = if index == 0 { " " } else { ", " };
+                    write!(f, "{separator}{}", path.display())?;
+                }
+                Ok(())
+            }
+            Self::InvalidHex { line_number, .. } => {
+                write!(f, "line {line_number}: invalid hexadecimal id")
+            }
+            Self::InvalidId { id, .. } => write!(f, "invalid hexadecimal id `{id}`"),
+            Self::MalformedSelector { selector } => {
+                write!(
+                    f,
+                    "malformed selector `{selector}`, expected `vendor:device`"
+                )
+            }
+            Self::MalformedSubsystem { subsystem } => write!(
+                f,
+                "malformed subsystem id `{subsystem}`, expected `subvendor:subdevice`"
+            ),
+            Self::MalformedAddress { address } => write!(
+                f,
+                "malformed PCI address `{address}`, expected `domain:bus:device.function`"
+            ),
+            Self::UnknownClass {
+                line_number: Some(line_number),
+                id,
+            } => write!(f, "line {line_number}: unknown class {id:02x}"),
+            Self::UnknownClass {
+                line_number: None,
+                id,
+            } => write!(f, "unknown class {id:02x}"),
+            Self::OrphanSubdevice { line_number } => {
+                write!(f, "line {line_number}: subdevice without a device above it")
+            }
+            Self::MissingSeparator { line_number } => {
+                write!(
+                    f,
+                    "line {line_number}: missing separator between id and name"
+                )
+            }
+            Self::UnexpectedIndent { line_number } => {
+                write!(f, "line {line_number}: unexpected indentation")
+            }
+            Self::TooDeeplyNested { line_number, depth } => write!(
+                f,
+                "line {line_number}: nested {depth} levels deep, at most 2 are allowed"
+            ),
+            Self::InvalidUtf8 {
+                line_number,
+                byte_offset,
+                ..
+            } => write!(f, "line {line_number}: invalid UTF-8 at byte {byte_offset}"),
+        }
+    }
+}
+
+impl core::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(err) => Some(err),
+            Self::InvalidHex { source, .. } | Self::InvalidId { source, .. } => Some(source),
+            Self::InvalidUtf8 { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for ParseError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Problem in a pci.ids file that [PciIds::parse_with_diagnostics] noticed and parsed past.
+#[derive(Debug)]
+pub enum Diagnostic {
+    /// An entry has the same id as an earlier entry with the same parent. Both entries are kept.
+    Duplicate {
+        /// Line of the duplicate, starting at 1.
+        line_number: usize,
+        /// Line of the first entry with that id.
+        first_line_number: usize,
+    },
+    /// An entry has a lower id than the entry with the same parent right before it.
+    OutOfOrder {
+        /// Line of the entry, starting at 1.
+        line_number: usize,
+        /// Line of the entry before it.
+        previous_line_number: usize,
+    },
+    /// A line could not be parsed and was left out, along with every line nested below it.
+    Skipped(ParseError),
+}
+
+/// Audit of the subvendor ids used by subdevices, and whether they correspond to a known [Vendor].
+///
+/// Every id is only counted once, no matter how many subdevices refer to it.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubvendorCoverage {
+    resolved: usize,
+    unresolved_ids: Vec<u16>,
+}
+
+impl SubvendorCoverage {
+    /// Number of distinct subvendor ids that have a matching vendor.
+    pub fn resolved(&self) -> usize {
+        self.resolved
+    }
+
+    /// Number of distinct subvendor ids without a matching vendor.
+    pub fn unresolved(&self) -> usize {
+        self.unresolved_ids.len()
+    }
+
+    /// The subvendor ids without a matching vendor, in ascending order.
+    pub fn unresolved_ids(&self) -> &[u16] {
+        &self.unresolved_ids
+    }
+}
+
+/// Names of a device and its subsystem as returned by [PciIds::resolve], `None` for every level
+/// that isn't in the database.
+///
+/// The names borrow from the [PciIds] they were resolved with, nothing is copied. They can be kept
+/// for as long as the database lives:
+/// ```
+/// use pci_id::pci_ids::PciIds;
+///
+/// let pci_ids = PciIds::parse_from_str(
+///     "0e11  Compaq Computer Corporation\n\t0046  Smart Array 64xx\n\
+///      10de  NVIDIA Corporation\n\t1b80  GP104 [GeForce GTX 1080]\n",
+/// )
+/// .unwrap();
+/// let names: Vec<&str> = [(0x0e11, 0x0046), (0x10de, 0x1b80)]
+///     .into_iter()
+///     .filter_map(|(vendor, device)| pci_ids.resolve(vendor, device, None, None).device)
+///     .collect();
+/// let vendor: &str = pci_ids.resolve(0x0e11, 0x0046, None, None).vendor.unwrap();
+/// assert!(core::ptr::eq(vendor, pci_ids.vendor_by_id(0x0e11).unwrap().name()));
+/// assert_eq!(names[0], "Smart Array 64xx");
+/// ```
+///
+/// but not any longer:
+/// ```compile_fail
+/// use pci_id::pci_ids::PciIds;
+///
+/// let pci_ids = PciIds::parse_from_str("0e11  Compaq\n\t0046  Smart Array 64xx\n").unwrap();
+/// let names = pci_ids.resolve(0x0e11, 0x0046, None, None);
+/// drop(pci_ids);
+/// assert_eq!(names.device, Some("Smart Array 64xx"));
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ResolvedNames<'a> {
+    /// Name of the vendor.
+    pub vendor: Option<&'a str>,
+    /// Name of the device.
+    pub device: Option<&'a str>,
+    /// Name of the subvendor, which is itself a vendor.
+    pub subvendor: Option<&'a str>,
+    /// Name of the subdevice.
+    pub subdevice: Option<&'a str>,
+}
+
+/// Names of the class, subclass and programming interface of a class code as returned by
+/// [PciIds::resolve_class], `None` for every level that isn't in the database.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ResolvedClass<'a> {
+    /// Name of the class.
+    pub class: Option<&'a str>,
+    /// Name of the subclass.
+    pub subclass: Option<&'a str>,
+    /// Name of the programming interface.
+    pub interface: Option<&'a str>,
+}
+
+impl ResolvedClass<'_> {
+    /// Join the names that were found, from the class down to the programming interface, with
+    /// `sep` in between, e.g. `Serial Bus Controller / USB controller / XHCI` for `" / "`.
+    ///
+    /// Levels without a name are left out, so nothing found gives an empty string.
+    pub fn full_name(&self, sep: &str) -> String {
+        [self.class, self.subclass, self.interface]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(sep)
+    }
+}
+
+/// Options to tweak how a pci.ids file is parsed.
+///
+/// The defaults match the behavior of [PciIds::parse_pci_id_list] and [PciIds::parse_from_bytes].
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseOptions {
+    /// Keep the comments of every entry other than a class, accessible through [Vendor::comment],
+    /// [Device::comment] and the like.
+    ///
+    /// The comment of an entry is made of the comment lines right above it and a `  # comment`
+    /// trailing the entry's name.
+    ///
+    /// Trailing comments are split off the names of every entry either way.
+    pub keep_comments: bool,
+    /// Fail with [ParseError::UnknownClass] on class ids that have no
+    /// [DeviceClass](crate::device_class::DeviceClass) variant.
+    ///
+    /// Meant as a tripwire for noticing when the upstream file gains a class this crate doesn't
+    /// know about yet, without it such a class becomes
+    /// [DeviceClass::Other](crate::device_class::DeviceClass::Other).
+    pub strict_classes: bool,
+    /// Collect subdevices without a device above them into [PciIds::orphan_subdevices] instead of
+    /// failing with [ParseError::OrphanSubdevice].
+    pub collect_orphans: bool,
+    /// Fail on lines whose indentation doesn't fit, like three tabs or spaces, with
+    /// [ParseError::TooDeeplyNested] or [ParseError::UnexpectedIndent]. On by default.
+    ///
+    /// Turned off, such lines are skipped instead, and so are the lines nested below them, which
+    /// would otherwise end up attached to the wrong parent.
+    pub strict: bool,
+    /// Replace invalid UTF-8 with `U+FFFD` instead of failing with [ParseError::InvalidUtf8].
+    /// On by default.
+    ///
+    /// Only [PciIds::parse_bytes_with_options] looks at it. The functions reading files always
+    /// replace invalid UTF-8, and those taking a [str] get valid UTF-8 to begin with.
+    pub lossy_utf8: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            keep_comments: false,
+            strict_classes: false,
+            collect_orphans: false,
+            strict: true,
+            lossy_utf8: true,
+        }
+    }
+}
+
 /// Wrapper struct around the list of PCI vendors and classes that exist in the pci.ids file.
+///
+/// # Reserved ids
+/// The upstream file has no special blocks, reserved ids are listed like any other entry. The
+/// invalid vendor id `ffff` is the last vendor, named `Illegal Vendor ID` and without devices, and
+/// class `ff` is the last class, `Unassigned class`, without subclasses. Nothing follows the class
+/// section, lines that would are parsed as part of it.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct PciIds {
     vendors: Vec<Vendor>,
     classes: Vec<Class>,
+    orphan_subdevices: Vec<SubDevice>,
+    version: Option<String>,
+    date: Option<String>,
+    source: Option<Box<str>>,
 }
 
 impl PciIds {
@@ -35,230 +458,4397 @@ impl PciIds {
         Self {
             vendors: Vec::new(),
             classes: Vec::new(),
+            orphan_subdevices: Vec::new(),
+            version: None,
+            date: None,
+            source: None,
+        }
+    }
+
+    /// Create a new PciIds struct with room for the given number of vendors and classes, for
+    /// filling it without reallocating, e.g. through [PciIds::parse_vendors_from_str].
+    pub fn with_capacity(vendors: usize, classes: usize) -> Self {
+        Self {
+            vendors: Vec::with_capacity(vendors),
+            classes: Vec::with_capacity(classes),
+            ..Self::new()
+        }
+    }
+
+    /// Create a PciIds struct from already assembled lists, see [PciIdsBuilder](crate::builder::PciIdsBuilder).
+    pub(crate) fn from_parts(vendors: Vec<Vendor>, classes: Vec<Class>) -> Self {
+        Self {
+            vendors,
+            classes,
+            ..Self::new()
         }
     }
 
     /// Returns a reference to the list of vendors.
+    ///
+    /// Prefer [PciIds::iter_vendors], which doesn't tie callers to the storage being a [Vec].
     pub fn vendors(&self) -> &Vec<Vendor> {
         &self.vendors
     }
 
+    /// Iterate over the vendors, in file order.
+    pub fn iter_vendors(&self) -> impl Iterator<Item = &Vendor> {
+        self.vendors.iter()
+    }
+
+    /// Iterate over the devices of every vendor along with their vendor, in file order.
+    pub fn all_devices(&self) -> impl Iterator<Item = (&Vendor, &Device)> {
+        self.vendors
+            .iter()
+            .flat_map(|v| v.iter_devices().map(move |d| (v, d)))
+    }
+
+    /// Iterate over the subdevices of every device along with their device and vendor, in file
+    /// order.
+    ///
+    /// # Example
+    /// ```
+    /// use pci_id::pci_ids::PciIds;
+    ///
+    /// let pci_ids = PciIds::parse_from_str(
+    ///     "1002  Advanced Micro Devices, Inc. [AMD/ATI]\n\
+    ///      \t731f  Navi 10 [Radeon RX 5600 OEM/5600 XT / 5700/5700 XT]\n\
+    ///      \t\t1da2 e409  Sapphire Technology Limited Pulse Radeon RX 5700 XT\n\
+    ///      \t\t1462 3811  Radeon RX 5700 XT\n",
+    /// )
+    /// .unwrap();
+    /// let sapphire_cards = pci_ids
+    ///     .iter_subdevices()
+    ///     .filter(|(_, _, s)| s.subvendor_id() == 0x1da2);
+    /// assert_eq!(sapphire_cards.count(), 1);
+    /// ```
+    pub fn iter_subdevices(&self) -> impl Iterator<Item = (&Vendor, &Device, &SubDevice)> {
+        self.all_devices()
+            .flat_map(|(v, d)| d.subdevices().iter().map(move |s| (v, d, s)))
+    }
+
     /// Returns a reference to the list of classes.
+    ///
+    /// Prefer [PciIds::iter_classes], which doesn't tie callers to the storage being a [Vec].
     pub fn classes(&self) -> &Vec<Class> {
         &self.classes
     }
 
-    /// Given the path to a valid pci.ids repository file will only parse the [Vendor]s into `self`,
-    /// skipping the [Class]es.
-    pub fn parse_vendors(&mut self, path: &Path) -> Result<(), io::Error> {
-        let data = std::fs::read_to_string(path)?;
-        self.parse_lines(data, false, true).unwrap();
-        Ok(())
+    /// Iterate over the classes, in file order.
+    pub fn iter_classes(&self) -> impl Iterator<Item = &Class> {
+        self.classes.iter()
     }
 
-    /// Given the path to a valid pci.ids repository file will only parse the [Class]es into `self`,
-    /// skipping the [Vendor]s.
-    pub fn parse_classes(&mut self, path: &Path) -> Result<(), io::Error> {
-        let data = std::fs::read_to_string(path)?;
-        self.parse_lines(data, true, false).unwrap();
-        Ok(())
+    /// Iterate over every programming interface along with its class and subclass, in file order.
+    pub fn iter_interfaces(&self) -> impl Iterator<Item = (&Class, &SubClass, &Interface)> {
+        self.classes.iter().flat_map(|c| {
+            c.subclasses()
+                .iter()
+                .flat_map(move |s| s.interfaces().iter().map(move |i| (c, s, i)))
+        })
     }
 
-    #[inline(always)]
-    fn parse_lines(
-        &mut self,
-        data: String,
-        skip_vendors: bool,
-        skip_classes: bool,
-    ) -> Result<(), ParseIntError> {
-        let mut in_class_section = false;
-        let mut vendor: Vendor;
-        let mut device: Device;
-        let mut class: Class;
-        let mut subclass: SubClass;
-
-        let mut devices = Vec::new();
-        let mut subdevices = Vec::new();
-        let mut subclasses = Vec::new();
-        let mut interfaces = Vec::new();
+    /// Subdevices that had no device to belong to, in file order.
+    ///
+    /// # Note
+    /// These are only collected when parsing with [ParseOptions::collect_orphans].
+    pub fn orphan_subdevices(&self) -> &[SubDevice] {
+        &self.orphan_subdevices
+    }
 
-        // TODO: Split up list mutation into an inlined function
-        for line in data.lines() {
-            // Skip comments and empty lines
-            if line.starts_with('#') || line.is_empty() {
-                continue;
-            }
+    /// Number of vendors.
+    pub fn vendor_count(&self) -> usize {
+        self.vendors.len()
+    }
 
-            // Should be safe since we check if the line is empty thus the next char is guaranteed
-            // to be there
-            let mut chars = line.chars();
-            let char = chars.next().unwrap();
+    /// Ids of the vendors, in file order.
+    pub fn vendor_ids(&self) -> Vec<u16> {
+        self.vendors.iter().map(Vendor::id).collect()
+    }
 
-            let (id, name) = line.split_once("  ").unwrap();
-            let name = name.trim();
+    /// Ids of the vendors in ascending order, like [Vendor::device_ids_sorted] for devices.
+    pub fn vendor_ids_sorted(&self) -> Vec<u16> {
+        let mut ids = self.vendor_ids();
+        ids.sort_unstable();
+        ids
+    }
 
-            // Line starts with a digit
-            if !skip_vendors && char.is_digit(16) && char != 'C' && !in_class_section {
-                let id = u16::from_str_radix(id.trim(), 16)?;
-                if let Some(v) = self.vendors.last_mut() {
-                    v.set_devices(devices);
-                }
-                vendor = Vendor::new(id, name.to_owned());
-                self.vendors.push(vendor);
-                devices = Vec::new();
-            } else if !skip_vendors && char == '\t' && !in_class_section {
-                // One tab
-                if chars.next().unwrap() != '\t' {
-                    let id = u16::from_str_radix(id.trim(), 16)?;
-                    if let Some(d) = devices.last_mut() {
-                        d.set_subdevices(subdevices);
-                    }
-                    device = Device::new(id, name.to_owned());
-                    devices.push(device);
-                    subdevices = Vec::new();
-                // Two tabs
-                } else {
-                    let (subvendor_id, subdevice_id) = id.split_once(" ").unwrap();
-                    let subvendor_id = u16::from_str_radix(subvendor_id.trim(), 16)?;
-                    let subdevice_id = u16::from_str_radix(subdevice_id.trim(), 16)?;
-                    let subdevice = SubDevice::new(subvendor_id, subdevice_id, name.to_owned());
-                    subdevices.push(subdevice);
-                }
+    /// Number of devices, summed over every vendor.
+    pub fn device_count(&self) -> usize {
+        self.vendors.iter().map(|v| v.devices().len()).sum()
+    }
 
-            // Line starts with a C meaning we are in the class section
-            } else if char == 'C' {
-                if skip_classes {
-                    break;
-                }
+    /// Number of subdevices, summed over every device. [PciIds::orphan_subdevices] aren't counted.
+    pub fn subdevice_count(&self) -> usize {
+        self.all_devices().map(|(_, d)| d.subdevices().len()).sum()
+    }
 
-                if !in_class_section {
-                    in_class_section = true;
-                }
+    /// Number of classes.
+    pub fn class_count(&self) -> usize {
+        self.classes.len()
+    }
 
-                let (_, id) = id.split_once(" ").unwrap();
-                let id = u8::from_str_radix(id.trim(), 16)?;
-                if let Some(c) = self.classes.last_mut() {
-                    c.set_subclasses(subclasses);
-                }
-                class = Class::new(id);
-                self.classes.push(class);
-                subclasses = Vec::new();
-
-            // At this point every line should start with a tab, so no need to check for that
-            } else if !skip_classes && in_class_section {
-                let id = u8::from_str_radix(id.trim(), 16)?;
-                // One tab
-                if chars.next().unwrap() != '\t' {
-                    if let Some(s) = subclasses.last_mut() {
-                        s.set_interfaces(interfaces);
-                    }
-                    subclass = SubClass::new(id, name.to_owned());
-                    subclasses.push(subclass);
-                    interfaces = Vec::new();
+    /// Number of subclasses, summed over every class.
+    pub fn subclass_count(&self) -> usize {
+        self.classes.iter().map(Class::subclass_count).sum()
+    }
+
+    /// Number of programming interfaces, summed over every subclass.
+    pub fn interface_count(&self) -> usize {
+        self.classes
+            .iter()
+            .flat_map(|c| c.subclasses())
+            .map(|s| s.interfaces().len())
+            .sum()
+    }
+
+    /// Whether there are neither vendors nor classes, e.g. because an empty file was parsed.
+    pub fn is_empty(&self) -> bool {
+        self.vendors.is_empty() && self.classes.is_empty()
+    }
+
+    /// Returns the vendor at the given position in the list of vendors.
+    pub fn vendor_at(&self, index: usize) -> Option<&Vendor> {
+        self.vendors.get(index)
+    }
+
+    /// Create a [CachedResolver] for repeatedly resolving the same ids against `self`.
+    #[cfg(feature = "std")]
+    pub fn cached_resolver(&self) -> CachedResolver<'_> {
+        CachedResolver::new(self)
+    }
+
+    /// Build an index over the vendors, devices and subdevices for constant time lookups.
+    #[cfg(feature = "std")]
+    pub fn index(&self) -> IndexedPciIds<'_> {
+        IndexedPciIds::new(self)
+    }
+
+    /// Compare `self`, as the old version of the database, to `other` as the new one, listing the
+    /// vendors, devices and subdevices that were added, removed or renamed.
+    pub fn diff<'a>(&'a self, other: &'a PciIds) -> PciIdsDiff<'a> {
+        PciIdsDiff::new(self, other)
+    }
+
+    /// Walk over every entry of the database, calling the matching [PciIdsVisitor] method for each.
+    pub fn visit<V: PciIdsVisitor>(&self, visitor: &mut V) {
+        for vendor in &self.vendors {
+            visitor.visit_vendor(vendor);
+            for device in vendor.devices() {
+                visitor.visit_device(vendor, device);
+                for subdevice in device.subdevices() {
+                    visitor.visit_subdevice(vendor, device, subdevice);
                 }
-                // Two tabs
-                else {
-                    let interface = Interface::new(id, name.to_owned());
-                    interfaces.push(interface);
+            }
+        }
+        for class in &self.classes {
+            visitor.visit_class(class);
+            for subclass in class.subclasses() {
+                visitor.visit_subclass(class, subclass);
+                for interface in subclass.interfaces() {
+                    visitor.visit_interface(class, subclass, interface);
                 }
             }
         }
-        // Add in the last ones
-        if let Some(d) = devices.last_mut() {
-            d.set_subdevices(subdevices);
+    }
+
+    /// Returns the class hierarchy as a tree of owned nodes, in file order.
+    ///
+    /// Unlike [Class], every level of the tree carries its id and name directly, which makes it
+    /// convenient to hand to a frontend.
+    pub fn class_tree(&self) -> Vec<ClassNode> {
+        self.classes.iter().map(ClassNode::from).collect()
+    }
+
+    /// Returns every programming interface with the given name, ignoring ASCII case, along with the
+    /// class and subclass it belongs to.
+    ///
+    /// Interface names like `Vendor specific` recur under several subclasses, this gives the full
+    /// set of class codes a name maps to.
+    pub fn interfaces_named(&self, name: &str) -> Vec<(&Class, &SubClass, &Interface)> {
+        self.classes
+            .iter()
+            .flat_map(|c| c.subclasses().iter().map(move |s| (c, s)))
+            .flat_map(|(c, s)| s.interfaces().iter().map(move |i| (c, s, i)))
+            .filter(|(_, _, i)| i.name().eq_ignore_ascii_case(name))
+            .collect()
+    }
+
+    /// Returns the vendors whose ids fall within the given range.
+    ///
+    /// # Note
+    /// The bounds are found by binary search, so this relies on the vendors being sorted by id,
+    /// as they are in the upstream pci.ids file. Vendors appended by merging in other files may
+    /// fall outside the slice.
+    pub fn vendors_in_range(&self, range: core::ops::Range<u16>) -> &[Vendor] {
+        let start = self.vendors.partition_point(|v| v.id() < range.start);
+        let end = self.vendors.partition_point(|v| v.id() < range.end);
+        &self.vendors[start..end.max(start)]
+    }
+
+    /// Returns the vendor with the given id.
+    ///
+    /// # Example
+    /// ```
+    /// use pci_id::pci_ids::PciIds;
+    ///
+    /// let pci_ids = PciIds::parse_from_str("0e11  Compaq Computer Corporation\n").unwrap();
+    /// let compaq = pci_ids.vendor_by_id(0x0e11).unwrap();
+    /// assert_eq!(compaq.name(), "Compaq Computer Corporation");
+    /// ```
+    pub fn vendor_by_id(&self, id: impl Into<VendorId>) -> Option<&Vendor> {
+        let VendorId(id) = id.into();
+        self.vendors.iter().find(|v| v.id() == id)
+    }
+
+    /// Returns the vendor with the given id, found by binary search instead of a linear scan.
+    ///
+    /// # Note
+    /// The vendors have to be sorted by id, which [PciIds::sort] guarantees. Otherwise the result
+    /// is unspecified, the vendor may not be found even though it exists. If an id occurs more
+    /// than once, any of its vendors may be returned.
+    pub fn vendor_by_id_sorted(&self, id: impl Into<VendorId>) -> Option<&Vendor> {
+        let VendorId(id) = id.into();
+        self.vendors
+            .binary_search_by_key(&id, |v| v.id())
+            .ok()
+            .map(|index| &self.vendors[index])
+    }
+
+    /// Sort the vendors, classes and every list nested in them by id.
+    ///
+    /// The sort is stable, entries with equal ids keep their order.
+    pub fn sort(&mut self) {
+        self.vendors.sort_by_key(|v| v.id());
+        self.vendors.iter_mut().for_each(Vendor::sort);
+        self.classes.sort_by_key(|c| c.id());
+        self.classes.iter_mut().for_each(Class::sort);
+    }
+
+    /// Resolve the names for the four ids identifying a device, e.g. as read from sysfs.
+    ///
+    /// The subvendor is looked up among all vendors, the subdevice needs both of its ids. Unlike
+    /// the vendor and device, the subsystem ids have to be typed, so they can't be swapped.
+    ///
+    /// # Example
+    /// ```
+    /// use pci_id::ids::{SubDeviceId, VendorId};
+    /// use pci_id::pci_ids::PciIds;
+    ///
+    /// let pci_ids = PciIds::parse_from_str(
+    ///     "0e11  Compaq Computer Corporation\n\
+    ///      \t0046  Smart Array 64xx\n\
+    ///      \t\t0e11 409a  Smart Array 641\n",
+    /// )
+    /// .unwrap();
+    /// let names = pci_ids.resolve(0x0e11, 0x0046, Some(VendorId(0x0e11)), Some(SubDeviceId(0x409a)));
+    /// assert_eq!(names.device, Some("Smart Array 64xx"));
+    /// assert_eq!(names.subdevice, Some("Smart Array 641"));
+    /// ```
+    pub fn resolve(
+        &self,
+        vendor: impl Into<VendorId>,
+        device: impl Into<DeviceId>,
+        subvendor: Option<VendorId>,
+        subdevice: Option<SubDeviceId>,
+    ) -> ResolvedNames<'_> {
+        let vendor = self.vendor_by_id(vendor);
+        let device = vendor.and_then(|v| v.device_by_id(device));
+        let subdevice = match (subvendor, subdevice) {
+            (Some(VendorId(subvendor)), Some(SubDeviceId(subdevice))) => {
+                device.and_then(|d| d.subdevice_by_ids(subvendor, subdevice))
+            }
+            _ => None,
         };
-        if let Some(v) = self.vendors.last_mut() {
-            v.set_devices(devices);
+        ResolvedNames {
+            vendor: vendor.map(Vendor::name),
+            device: device.map(Device::name),
+            subvendor: subvendor
+                .and_then(|id| self.vendor_by_id(id))
+                .map(Vendor::name),
+            subdevice: subdevice.map(SubDevice::name),
+        }
+    }
+
+    /// Returns the name of the vendor the subvendor id of the given subdevice refers to, e.g. the
+    /// board partner that built a card around another vendor's chip.
+    pub fn subvendor_name(&self, subdevice: &SubDevice) -> Option<&str> {
+        self.vendor_by_id(subdevice.subvendor_id())
+            .map(Vendor::name)
+    }
+
+    /// Returns the class with the given id.
+    pub fn class_by_id(&self, id: impl Into<ClassId>) -> Option<&Class> {
+        let ClassId(id) = id.into();
+        self.classes.iter().find(|c| c.id() == id)
+    }
+
+    /// Whether there is a vendor with the given id.
+    pub fn contains_vendor(&self, id: impl Into<VendorId>) -> bool {
+        let VendorId(id) = id.into();
+        self.vendors.iter().any(|v| v.id() == id)
+    }
+
+    /// Whether there is a class with the given id.
+    pub fn contains_class(&self, id: impl Into<ClassId>) -> bool {
+        let ClassId(id) = id.into();
+        self.classes.iter().any(|c| c.id() == id)
+    }
+
+    /// Resolve the names for a 24 bit class code, e.g. as read from `/sys/bus/pci/devices/*/class`.
+    ///
+    /// The class is taken from the third byte, the subclass from the second and the programming
+    /// interface from the lowest one. Anything above the lower 24 bits is ignored.
+    ///
+    /// # Example
+    /// ```
+    /// use pci_id::pci_ids::PciIds;
+    ///
+    /// let pci_ids =
+    ///     PciIds::parse_from_str("C 0c  Serial bus controller\n\t03  USB controller\n\t\t30  XHCI\n")
+    ///         .unwrap();
+    /// let names = pci_ids.resolve_class(0x0c0330);
+    /// assert_eq!(names.subclass, Some("USB controller"));
+    /// assert_eq!(names.interface, Some("XHCI"));
+    /// ```
+    pub fn resolve_class(&self, code: u32) -> ResolvedClass<'_> {
+        let [_, class, subclass, interface] = code.to_be_bytes();
+        let class = self.class_by_id(class);
+        let subclass = class.and_then(|c| c.subclass_by_id(subclass));
+        let interface = subclass.and_then(|s| s.interface_by_id(interface));
+        ResolvedClass {
+            class: class.map(|c| c.class().name()),
+            subclass: subclass.map(SubClass::name),
+            interface: interface.map(Interface::name),
+        }
+    }
+
+    /// Returns the position of the vendor with the given id in the list of vendors.
+    pub fn vendor_index(&self, id: u16) -> Option<usize> {
+        self.vendors.iter().position(|v| v.id() == id)
+    }
+
+    /// Resolve a vendor and device given as hexadecimal strings, e.g. `"10de"` and `"1b80"`.
+    ///
+    /// Surrounding whitespace, a `0x` prefix and uppercase digits are all accepted.
+    ///
+    /// # Errors
+    /// Malformed hex input is an error, while well-formed ids that aren't in the database result in
+    /// `Ok(None)`.
+    pub fn resolve_str(
+        &self,
+        vendor: &str,
+        device: &str,
+    ) -> Result<Option<(&Vendor, &Device)>, ParseError> {
+        let vendor = parse_hex_u16(vendor)?;
+        let device = parse_hex_u16(device)?;
+        Ok(self
+            .vendor_by_id(vendor)
+            .and_then(|v| v.device_by_id(device).map(|d| (v, d))))
+    }
+
+    /// Resolve a selector in the `vendor[:device]` notation used by lspci, e.g. `10de:1b80`.
+    ///
+    /// For a selector with only a vendor, such as `10de` or `10de:`, the device is `None`.
+    /// Otherwise both the vendor and the device have to exist for `Some` to be returned.
+    ///
+    /// # Errors
+    /// Selectors with an empty vendor or more than one `:` as well as malformed hex ids are errors.
+    pub fn resolve_colon(
+        &self,
+        selector: &str,
+    ) -> Result<Option<(&Vendor, Option<&Device>)>, ParseError> {
+        let malformed = || ParseError::MalformedSelector {
+            selector: selector.to_owned(),
         };
-        if let Some(s) = subclasses.last_mut() {
-            s.set_interfaces(interfaces);
+        let (vendor, device) = match selector.split_once(':') {
+            Some((_, device)) if device.contains(':') => return Err(malformed()),
+            Some((vendor, device)) => (vendor, Some(device).filter(|d| !d.trim().is_empty())),
+            None => (selector, None),
         };
-        if let Some(c) = self.classes.last_mut() {
-            c.set_subclasses(subclasses);
+        if vendor.trim().is_empty() {
+            return Err(malformed());
+        }
+
+        let vendor_id = parse_hex_u16(vendor)?;
+        let Some(vendor) = self.vendor_by_id(vendor_id) else {
+            return Ok(None);
         };
-        Ok(())
+        match device {
+            Some(device) => {
+                let device_id = parse_hex_u16(device)?;
+                Ok(vendor.device_by_id(device_id).map(|d| (vendor, Some(d))))
+            }
+            None => Ok(Some((vendor, None))),
+        }
     }
 
-    /// Try to parse the given pci.ids file to a [PciIds] instance.
+    /// Look up a kernel driver hint for a device.
     ///
-    /// The entire file is first read into a [String]. Parsing is then done line by line of the
-    /// string to the various data structures.
+    /// A hint is a line of the form `# driver: name` in the comment attached to the device, so this
+    /// only returns something for databases parsed with [ParseOptions::keep_comments].
+    pub fn driver_hint(&self, vendor: u16, device: u16) -> Option<&str> {
+        let comment = self.vendor_by_id(vendor)?.device_by_id(device)?.comment()?;
+        comment.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let value = value.trim();
+            (key.trim().eq_ignore_ascii_case("driver") && !value.is_empty()).then_some(value)
+        })
+    }
+
+    /// Returns the vendors that have at least one device whose name contains any of the given
+    /// keywords, ignoring ASCII case.
     ///
-    /// # Errors
-    /// Reading in the file can fail for all the usual IO reasons, check [std::io::ErrorKind].
-    pub fn parse_pci_id_list(path: &Path) -> Result<Self, io::Error> {
-        let mut pci_ids = Self::new();
+    /// The vendors are ordered by id, vendors sharing an id keep their order from the file.
+    ///
+    /// # Example
+    /// Passing `["Radeon", "GeForce", "Arc"]` gives a rough list of GPU vendors.
+    pub fn vendors_matching_keywords(&self, keywords: &[&str]) -> Vec<&Vendor> {
+        let mut vendors: Vec<&Vendor> = self
+            .vendors
+            .iter()
+            .filter(|v| {
+                v.devices().iter().any(|d| {
+                    keywords
+                        .iter()
+                        .any(|keyword| contains_ignore_ascii_case(d.name(), keyword))
+                })
+            })
+            .collect();
+        vendors.sort_by_key(|v| v.id());
+        vendors
+    }
 
-        let data = std::fs::read_to_string(path)?;
-        pci_ids.parse_lines(data, false, false).unwrap();
+    /// Returns the vendors that have at least one device with subdevices, in file order.
+    ///
+    /// The iterator is lazy, each vendor's devices are only scanned up to the first one that has
+    /// subdevices.
+    pub fn vendors_with_subdevices(&self) -> impl Iterator<Item = &Vendor> {
+        self.vendors
+            .iter()
+            .filter(|v| v.devices().iter().any(|d| !d.subdevices().is_empty()))
+    }
 
-        Ok(pci_ids)
+    /// Returns the [Device::codename] of every device that has one, along with its vendor and
+    /// device, in file order.
+    pub fn codenames(&self) -> Vec<(&Vendor, &Device, &str)> {
+        self.all_devices()
+            .filter_map(|(v, d)| d.codename().map(|codename| (v, d, codename)))
+            .collect()
     }
-}
 
-impl Default for PciIds {
-    fn default() -> Self {
-        Self::new()
+    /// Returns every vendor whose name contains `query`, ignoring ASCII case, in file order.
+    ///
+    /// An empty query matches every vendor. Vendors with an exactly known name can be looked up
+    /// without scanning through [IndexedPciIds::get_vendor_by_name].
+    pub fn find_vendors_by_name(&self, query: &str) -> Vec<&Vendor> {
+        self.vendors
+            .iter()
+            .filter(|v| contains_ignore_ascii_case(v.name(), query))
+            .collect()
     }
-}
 
+    /// Returns every device whose name contains `query`, ignoring ASCII case, along with its
+    /// vendor.
+    ///
+    /// The results are ordered by `(vendor id, device id)`, entries sharing both ids keep their
+    /// order from the file.
+    pub fn search_devices(&self, query: &str) -> Vec<(&Vendor, &Device)> {
+        self.search_devices_sorted_by(query, |(v, d)| (v.id(), d.id()))
+    }
 
-#[cfg(test)]
-mod tests {
-    use crate::pci_ids::{PciIds, PATH_TO_PCI_IDS};
-    use std::path::Path;
-    /// Test the vendors part of the parsed result by picking an example and checking if it is ok
-    #[test]
-    fn test_vendors_list() {
-        let mut pci_ids = PciIds::new();
-        pci_ids
-            .parse_vendors(Path::new(PATH_TO_PCI_IDS))
-            .unwrap();
-        let res = pci_ids.vendors().iter().find(|&v| {
-            v.id() == 0x0e11
-                && v.name() == "Compaq Computer Corporation"
-                && v.devices()
-                    .iter()
-                    .find(|&d| {
-                        d.id() == 0x0046
-                            && d.name() == "Smart Array 64xx"
-                            && d.subdevices()
-                                .iter()
-                                .find(|&s| {
-                                    s.subvendor_id() == 0x0e11
-                                        && s.subdevice_id() == 0x409d
-                                        && s.name() == "Smart Array 6400 EM"
-                                })
-                                .is_some()
-                    })
-                    .is_some()
-        });
-        assert!(res.is_some());
+    /// Same as [PciIds::search_devices] but ordered by the key returned from `key_fn` instead.
+    ///
+    /// The sort is stable, so results with equal keys keep their order from the file.
+    pub fn search_devices_sorted_by<K, F>(
+        &self,
+        query: &str,
+        mut key_fn: F,
+    ) -> Vec<(&Vendor, &Device)>
+    where
+        K: Ord,
+        F: FnMut(&(&Vendor, &Device)) -> K,
+    {
+        let mut results: Vec<(&Vendor, &Device)> = self
+            .all_devices()
+            .filter(|(_, d)| contains_ignore_ascii_case(d.name(), query))
+            .collect();
+        results.sort_by_key(|result| key_fn(result));
+        results
     }
 
-    /// Test the classes part of the parsed result by picking an example and checking if it is ok
-    #[test]
-    fn test_classes_list() {
-        let mut pci_ids = PciIds::new();
-        pci_ids
-            .parse_classes(Path::new(PATH_TO_PCI_IDS))
-            .unwrap();
-        let res = pci_ids.classes().iter().find(|&c| {
-            u8::from(c.class()) == 0x0c
-                && c.class().to_string() == "Serial Bus Controller"
-                && c.subclasses()
+    /// Name of the vendor with the given id, or `Unknown vendor (HHHH)` if there is no such vendor.
+    ///
+    /// Only the fallback is allocated, a found name is borrowed from `self`.
+    pub fn vendor_name_or_unknown(&self, id: u16) -> Cow<'_, str> {
+        match self.vendor_by_id(id) {
+            Some(v) => Cow::Borrowed(v.name()),
+            None => Cow::Owned(format!("Unknown vendor ({id:04x})")),
+        }
+    }
+
+    /// Name of the given vendor's device, or `Unknown device (HHHH)` if either the vendor or the
+    /// device is missing.
+    ///
+    /// Only the fallback is allocated, a found name is borrowed from `self`.
+    pub fn device_name_or_unknown(&self, vendor: u16, device: u16) -> Cow<'_, str> {
+        let found = self
+            .vendor_by_id(vendor)
+            .and_then(|v| v.device_by_id(device));
+        match found {
+            Some(d) => Cow::Borrowed(d.name()),
+            None => Cow::Owned(format!("Unknown device ({device:04x})")),
+        }
+    }
+
+    /// Check every subvendor id used by a subdevice against the list of vendors.
+    ///
+    /// Useful to get a feel for how reliable resolving subvendor names will be for a given file.
+    pub fn subvendor_coverage(&self) -> SubvendorCoverage {
+        let vendor_ids: BTreeSet<u16> = self.vendors.iter().map(|v| v.id()).collect();
+        let subvendor_ids: BTreeSet<u16> = self
+            .vendors
+            .iter()
+            .flat_map(|v| v.devices())
+            .flat_map(|d| d.subdevices())
+            .map(|s| s.subvendor_id())
+            .collect();
+
+        let mut coverage = SubvendorCoverage::default();
+        for id in subvendor_ids {
+            if vendor_ids.contains(&id) {
+                coverage.resolved += 1;
+            } else {
+                coverage.unresolved_ids.push(id);
+            }
+        }
+        coverage
+    }
+
+    /// Build a new [PciIds] containing only the vendors, devices and subdevices of the hardware
+    /// installed in the running system, along with all of the classes.
+    ///
+    /// Meant for trimming the database down to a few kilobytes for a known machine, e.g. when
+    /// baking it into an appliance image.
+    ///
+    /// # Errors
+    /// Fails if the devices in `/sys/bus/pci/devices` can not be read.
+    #[cfg(feature = "sysfs")]
+    pub fn subset_for_system(&self) -> io::Result<PciIds> {
+        let devices = sysfs::read_devices(Path::new(sysfs::PATH_TO_SYSFS_DEVICES))?;
+        Ok(self.subset_for_devices(&devices))
+    }
+
+    /// Keep the entries matching the given devices. A vendor stays as soon as one of its devices
+    /// is present, even if the device itself is unknown to the database.
+    #[cfg(feature = "sysfs")]
+    fn subset_for_devices(&self, system: &[SystemDevice]) -> PciIds {
+        let mut vendors = Vec::new();
+        for vendor in &self.vendors {
+            let mut devices = Vec::new();
+            for device in vendor.devices() {
+                let installed: Vec<&SystemDevice> = system
                     .iter()
-                    .find(|&s| {
-                        s.id() == 0x03
-                            && s.name() == "USB controller"
-                            && s.interfaces()
-                                .iter()
-                                .find(|&i| i.id() == 0xfe && i.name() == "USB Device")
-                                .is_some()
+                    .filter(|s| s.vendor_id == vendor.id() && s.device_id == device.id())
+                    .collect();
+                if installed.is_empty() {
+                    continue;
+                }
+                let subdevices = device
+                    .subdevices()
+                    .iter()
+                    .filter(|sub| {
+                        installed.iter().any(|s| {
+                            s.subsystem.is_some_and(|id| {
+                                id.subvendor_id() == sub.subvendor_id()
+                                    && id.subdevice_id() == sub.subdevice_id()
+                            })
+                        })
                     })
-                    .is_some()
+                    .cloned()
+                    .collect();
+                let mut device = device.clone();
+                device.set_subdevices(subdevices);
+                devices.push(device);
+            }
+            if system.iter().any(|s| s.vendor_id == vendor.id()) {
+                let mut vendor = vendor.clone();
+                vendor.set_devices(devices);
+                vendors.push(vendor);
+            }
+        }
+
+        Self {
+            vendors,
+            classes: self.classes.clone(),
+            version: self.version.clone(),
+            date: self.date.clone(),
+            ..Self::new()
+        }
+    }
+
+    /// Approximate number of bytes the database occupies in memory.
+    ///
+    /// This is the size of the struct itself plus everything it owns on the heap, i.e. the
+    /// allocated capacity of every list and name. Allocator overhead is not included.
+    pub fn approximate_memory_usage(&self) -> usize {
+        core::mem::size_of::<Self>()
+            + self.vendors.capacity() * core::mem::size_of::<Vendor>()
+            + self.vendors.iter().map(Vendor::heap_size).sum::<usize>()
+            + self.classes.capacity() * core::mem::size_of::<Class>()
+            + self.classes.iter().map(Class::heap_size).sum::<usize>()
+            + self.orphan_subdevices.capacity() * core::mem::size_of::<SubDevice>()
+            + self
+                .orphan_subdevices
+                .iter()
+                .map(SubDevice::heap_size)
+                .sum::<usize>()
+            + self.version.as_ref().map_or(0, String::capacity)
+            + self.date.as_ref().map_or(0, String::capacity)
+            + self.source.as_ref().map_or(0, |s| s.len())
+    }
+
+    /// Write the database out in the pci.ids text format, which [PciIds::parse_from_str] reads
+    /// back to the same data.
+    ///
+    /// Every level is sorted by id, the version and date become the header and comments kept
+    /// through [ParseOptions::keep_comments] are written above their entries. Class names come
+    /// from [DeviceClass](crate::device_class::DeviceClass), and
+    /// [orphan subdevices](PciIds::orphan_subdevices) are left out since the format has no place
+    /// for them.
+    pub fn to_pci_ids_string(&self) -> String {
+        let mut out = String::new();
+        self.write_pci_ids(&mut out)
+            .expect("writing to a String can't fail");
+        out
+    }
+
+    fn write_pci_ids(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        fn write_comment(out: &mut impl fmt::Write, comment: Option<&str>) -> fmt::Result {
+            for line in comment.into_iter().flat_map(str::lines) {
+                match line {
+                    "" => writeln!(out, "#")?,
+                    line => writeln!(out, "# {line}")?,
+                }
+            }
+            Ok(())
+        }
+        fn sorted<T, K: Ord>(items: &[T], key: impl Fn(&T) -> K) -> Vec<&T> {
+            let mut items: Vec<&T> = items.iter().collect();
+            items.sort_by_key(|item| key(item));
+            items
+        }
+
+        if let Some(version) = &self.version {
+            writeln!(out, "# Version: {version}")?;
+        }
+        if let Some(date) = &self.date {
+            writeln!(out, "# Date: {date}")?;
+        }
+        if self.version.is_some() || self.date.is_some() {
+            writeln!(out)?;
+        }
+
+        for vendor in sorted(&self.vendors, Vendor::id) {
+            write_comment(out, vendor.comment())?;
+            writeln!(out, "{:04x}  {}", vendor.id(), vendor.name())?;
+            for device in sorted(vendor.devices(), Device::id) {
+                write_comment(out, device.comment())?;
+                writeln!(out, "\t{:04x}  {}", device.id(), device.name())?;
+                for subdevice in sorted(device.subdevices(), |s| {
+                    (s.subvendor_id(), s.subdevice_id())
+                }) {
+                    write_comment(out, subdevice.comment())?;
+                    writeln!(
+                        out,
+                        "\t\t{:04x} {:04x}  {}",
+                        subdevice.subvendor_id(),
+                        subdevice.subdevice_id(),
+                        subdevice.name()
+                    )?;
+                }
+            }
+        }
+
+        for class in sorted(&self.classes, Class::id) {
+            writeln!(out, "C {:02x}  {}", class.id(), class.class().name())?;
+            for subclass in sorted(class.subclasses(), SubClass::id) {
+                write_comment(out, subclass.comment())?;
+                writeln!(out, "\t{:02x}  {}", subclass.id(), subclass.name())?;
+                for interface in sorted(subclass.interfaces(), Interface::id) {
+                    write_comment(out, interface.comment())?;
+                    writeln!(out, "\t\t{:02x}  {}", interface.id(), interface.name())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Export the database as pretty printed JSON, for tools outside of Rust.
+    ///
+    /// Unlike the [serde::Serialize] implementation, which mirrors the structs, this is a nested
+    /// object keyed by lowercase hex ids like in the pci.ids file itself, so it can be diffed
+    /// against it. Subdevices are keyed by both of their ids separated by a space, and keys are
+    /// sorted. If an id occurs more than once, the first entry wins.
+    ///
+    /// ```json
+    /// {
+    ///   "classes": { "0c": { "name": "Serial Bus Controller", "subclasses": {
+    ///     "03": { "name": "USB controller", "interfaces": { "30": "XHCI" } } } } },
+    ///   "date": null,
+    ///   "vendors": { "10de": { "name": "NVIDIA Corporation", "devices": {
+    ///     "1b80": { "name": "GP104 [GeForce GTX 1080]", "subdevices": {
+    ///       "1043 8591": "ROG STRIX GTX 1080" } } } } },
+    ///   "version": "2024.01.01"
+    /// }
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        use serde_json::{Map, Value};
+
+        let mut vendors = Map::new();
+        for vendor in &self.vendors {
+            vendors.entry(vendor.hex_id()).or_insert_with(|| {
+                let mut devices = Map::new();
+                for device in vendor.devices() {
+                    devices.entry(device.hex_id()).or_insert_with(|| {
+                        let mut subdevices = Map::new();
+                        for subdevice in device.subdevices() {
+                            let key = format!(
+                                "{:04x} {:04x}",
+                                subdevice.subvendor_id(),
+                                subdevice.subdevice_id()
+                            );
+                            subdevices
+                                .entry(key)
+                                .or_insert_with(|| subdevice.name().into());
+                        }
+                        let mut device_json = Map::new();
+                        device_json.insert("name".into(), device.name().into());
+                        device_json.insert("subdevices".into(), subdevices.into());
+                        device_json.into()
+                    });
+                }
+                let mut vendor_json = Map::new();
+                vendor_json.insert("name".into(), vendor.name().into());
+                vendor_json.insert("devices".into(), devices.into());
+                vendor_json.into()
+            });
+        }
+
+        let mut classes = Map::new();
+        for class in &self.classes {
+            let key = format!("{:02x}", class.id());
+            classes.entry(key).or_insert_with(|| {
+                let mut subclasses = Map::new();
+                for subclass in class.subclasses() {
+                    subclasses.entry(subclass.hex_id()).or_insert_with(|| {
+                        let mut interfaces = Map::new();
+                        for interface in subclass.interfaces() {
+                            interfaces
+                                .entry(interface.hex_id())
+                                .or_insert_with(|| interface.name().into());
+                        }
+                        let mut subclass_json = Map::new();
+                        subclass_json.insert("name".into(), subclass.name().into());
+                        subclass_json.insert("interfaces".into(), interfaces.into());
+                        subclass_json.into()
+                    });
+                }
+                let mut class_json = Map::new();
+                class_json.insert("name".into(), class.class().name().into());
+                class_json.insert("subclasses".into(), subclasses.into());
+                class_json.into()
+            });
+        }
+
+        let mut root = Map::new();
+        root.insert("version".into(), self.version.clone().into());
+        root.insert("date".into(), self.date.clone().into());
+        root.insert("vendors".into(), vendors.into());
+        root.insert("classes".into(), classes.into());
+        serde_json::to_string_pretty(&Value::Object(root))
+    }
+
+    /// Store names that occur many times only once, shared between all entries using them.
+    ///
+    /// Lots of subdevice names repeat verbatim, e.g. for every card built around the same chip.
+    /// Only names for which sharing takes less memory than the copies are interned. Names keep
+    /// comparing, hashing and printing as before.
+    ///
+    /// Returns the number of bytes saved according to [PciIds::approximate_memory_usage].
+    pub fn intern(&mut self) -> usize {
+        let before = self.approximate_memory_usage();
+        let pool = NamePool::for_repeated(self.names());
+        self.vendors.iter_mut().for_each(|v| v.intern_names(&pool));
+        self.classes.iter_mut().for_each(|c| c.intern_names(&pool));
+        self.orphan_subdevices
+            .iter_mut()
+            .for_each(|s| s.intern_names(&pool));
+        drop(pool);
+        before.saturating_sub(self.approximate_memory_usage())
+    }
+
+    /// Every name of a vendor, device, subdevice, subclass or interface.
+    fn names(&self) -> impl Iterator<Item = &str> {
+        let vendors = self.vendors.iter().flat_map(|v| {
+            core::iter::once(v.name()).chain(v.devices().iter().flat_map(|d| {
+                core::iter::once(d.name()).chain(d.subdevices().iter().map(SubDevice::name))
+            }))
         });
-        println!("{:?}", res);
-        assert!(res.is_some());
+        let classes = self.classes.iter().flat_map(|c| {
+            c.subclasses().iter().flat_map(|s| {
+                core::iter::once(s.name()).chain(s.interfaces().iter().map(Interface::name))
+            })
+        });
+        vendors
+            .chain(classes)
+            .chain(self.orphan_subdevices.iter().map(SubDevice::name))
+    }
+
+    /// Version of the database, taken from the `# Version:` line at the top of the file.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Date the database was generated, taken from the `# Date:` line at the top of the file.
+    pub fn date(&self) -> Option<&str> {
+        self.date.as_deref()
+    }
+
+    /// Returns the text the database was parsed from, if it was kept around.
+    ///
+    /// # Note
+    /// This is only available when parsed with [PciIds::parse_retaining_source].
+    pub fn raw_source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// Given the path to a valid pci.ids repository file will only parse the [Vendor]s into `self`,
+    /// skipping the [Class]es.
+    ///
+    /// # Errors
+    /// Fails if the file can not be read or is malformed.
+    #[cfg(feature = "std")]
+    pub fn parse_vendors(&mut self, path: &Path) -> Result<(), ParseError> {
+        self.parse_path(path, false, true)
+    }
+
+    /// Given the contents of a pci.ids file will only parse the [Vendor]s into `self`, skipping
+    /// the [Class]es.
+    ///
+    /// # Errors
+    /// Fails if the contents are malformed.
+    pub fn parse_vendors_from_str(&mut self, data: &str) -> Result<(), ParseError> {
+        self.parse_lines(data, false, true, ParseOptions::default())
+    }
+
+    /// Given the path to a valid pci.ids repository file will only parse the [Class]es into `self`,
+    /// skipping the [Vendor]s.
+    ///
+    /// # Errors
+    /// Fails if the file can not be read or is malformed.
+    #[cfg(feature = "std")]
+    pub fn parse_classes(&mut self, path: &Path) -> Result<(), ParseError> {
+        self.parse_path(path, true, false)
+    }
+
+    /// Given the contents of a pci.ids file will only parse the [Class]es into `self`, skipping
+    /// the [Vendor]s.
+    ///
+    /// # Errors
+    /// Fails if the contents are malformed.
+    pub fn parse_classes_from_str(&mut self, data: &str) -> Result<(), ParseError> {
+        self.parse_lines(data, true, false, ParseOptions::default())
+    }
+
+    #[inline(always)]
+    fn parse_lines(
+        &mut self,
+        data: &str,
+        skip_vendors: bool,
+        skip_classes: bool,
+        options: ParseOptions,
+    ) -> Result<(), ParseError> {
+        self.parse_lines_with_progress(
+            data,
+            Classifier::default(),
+            skip_vendors,
+            skip_classes,
+            options,
+            (0, |_, _| {}),
+        )
+    }
+
+    /// Parse `data` into `self`, passing each call of the `progress` callback the total number of
+    /// lines it comes with.
+    ///
+    /// Only [PciIds::parse_with_progress] counts the lines up front, the other callers pass 0 along
+    /// with a callback that ignores it, so their input is only read once.
+    #[inline(always)]
+    fn parse_lines_with_progress<C: LineClassifier, F: FnMut(usize, usize)>(
+        &mut self,
+        data: &str,
+        mut classifier: C,
+        skip_vendors: bool,
+        skip_classes: bool,
+        options: ParseOptions,
+        (total_lines, mut progress): (usize, F),
+    ) -> Result<(), ParseError> {
+        let mut builder = TreeBuilder::new(options);
+        // Parse straight into the lists of `self` while they are empty, keeping the room made by
+        // PciIds::with_capacity, and only reserve what the estimate asks for on top of that
+        if self.vendors.is_empty() {
+            builder.vendors = core::mem::take(&mut self.vendors);
+        }
+        if self.classes.is_empty() {
+            builder.classes = core::mem::take(&mut self.classes);
+        }
+        let (vendors, classes) = estimate_capacity(data.len());
+        if !skip_vendors {
+            builder.vendors.reserve(vendors);
+        }
+        if !skip_classes {
+            builder.classes.reserve(classes);
+        }
+        // Depth of the last line skipped for its indentation, outside of strict mode
+        let mut skipped_depth = None;
+
+        for (index, line) in data.lines().enumerate() {
+            let line_number = index + 1;
+            if index % PROGRESS_INTERVAL == 0 {
+                progress(index, total_lines);
+            }
+            // Nothing but classes follows the first class line, so stop before even classifying it
+            if skip_classes && line.starts_with("C ") {
+                break;
+            }
+            let depth = line.bytes().take_while(|&b| b == b'\t').count();
+            if skipped_depth.is_some_and(|skipped| depth > skipped) && !line.trim().is_empty() {
+                continue;
+            }
+            skipped_depth = None;
+
+            let result =
+                classifier
+                    .classify(line_number, line)
+                    .and_then(|entry| match entry {
+                        Some(
+                            Entry::Vendor { .. } | Entry::Device { .. } | Entry::SubDevice { .. },
+                        ) if skip_vendors => Ok(()),
+                        Some(entry) => builder.push(line_number, entry),
+                        None => Ok(()),
+                    });
+            match result {
+                Err(error) if !options.strict && error.is_indentation() => {
+                    skipped_depth = Some(depth);
+                }
+                result => result?,
+            }
+        }
+
+        self.append(builder);
+        progress(total_lines, total_lines);
+        Ok(())
+    }
+
+    /// Add the entries collected by the builder to `self`, taking over its lists as they are if
+    /// `self` has none yet.
+    fn append(&mut self, mut builder: TreeBuilder) {
+        builder.finish();
+        if self.vendors.is_empty() {
+            self.vendors = builder.vendors;
+        } else {
+            self.vendors.append(&mut builder.vendors);
+        }
+        if self.classes.is_empty() {
+            self.classes = builder.classes;
+        } else {
+            self.classes.append(&mut builder.classes);
+        }
+        self.orphan_subdevices.extend(builder.orphan_subdevices);
+        self.version = self.version.take().or(builder.version);
+        self.date = self.date.take().or(builder.date);
+    }
+
+    /// Try to parse the given pci.ids file to a [PciIds] instance.
+    ///
+    /// The entire file is first read into memory. Parsing is then done line by line to the various
+    /// data structures, invalid UTF-8 is replaced like with [PciIds::parse_from_bytes].
+    ///
+    /// # Errors
+    /// Reading in the file can fail for all the usual IO reasons, check [std::io::ErrorKind], and a
+    /// malformed line is reported as a [ParseError] carrying its line number.
+    #[cfg(feature = "std")]
+    pub fn parse_pci_id_list(path: &Path) -> Result<Self, ParseError> {
+        Self::parse_selective(path, true, true)
+    }
+
+    /// Find and parse the pci.ids file of the system, looking at the path in the [PCI_IDS_PATH_VAR]
+    /// environment variable first and then at each of the [KNOWN_PATHS].
+    ///
+    /// The first of these that is a file gets parsed, an unset or empty variable is skipped.
+    /// [find_pci_ids_path] tells which file that is without parsing it.
+    ///
+    /// # Errors
+    /// Fails with [ParseError::NotFound], listing every path tried, if none of them is a file, or
+    /// like [PciIds::parse_pci_id_list] if the file found can't be read or is malformed.
+    #[cfg(feature = "std")]
+    pub fn from_default_path() -> Result<Self, ParseError> {
+        Self::parse_first_existing(default_paths())
+    }
+
+    /// Parse the first of the given paths that is a file.
+    #[cfg(feature = "std")]
+    fn parse_first_existing(paths: impl IntoIterator<Item = PathBuf>) -> Result<Self, ParseError> {
+        let mut tried = Vec::new();
+        for path in paths {
+            if path.is_file() {
+                return Self::parse_pci_id_list(&path);
+            }
+            tried.push(path);
+        }
+        Err(ParseError::NotFound { tried })
+    }
+
+    /// Given the path to a valid pci.ids repository file will parse the [Vendor]s, the [Class]es,
+    /// both or neither, reading the file only once.
+    ///
+    /// `parse_selective(path, true, false)` gives the same result as [PciIds::parse_vendors] into
+    /// a new instance, `parse_selective(path, false, true)` as [PciIds::parse_classes].
+    ///
+    /// # Errors
+    /// Fails if the file can not be read or the parsed sections are malformed.
+    #[cfg(feature = "std")]
+    pub fn parse_selective(
+        path: &Path,
+        want_vendors: bool,
+        want_classes: bool,
+    ) -> Result<Self, ParseError> {
+        let mut pci_ids = Self::new();
+        pci_ids.parse_path(path, !want_vendors, !want_classes)?;
+        Ok(pci_ids)
+    }
+
+    /// Read the file at `path` in one go and parse it into `self`, skipping the given sections.
+    #[cfg(feature = "std")]
+    fn parse_path(
+        &mut self,
+        path: &Path,
+        skip_vendors: bool,
+        skip_classes: bool,
+    ) -> Result<(), ParseError> {
+        let data = std::fs::read(path)?;
+        let options = ParseOptions::default();
+        let text = decode_utf8(&data, options.lossy_utf8)?;
+        self.parse_lines(&text, skip_vendors, skip_classes, options)
+    }
+
+    /// Parse the given contents of a pci.ids file to a [PciIds] instance, for when the file has
+    /// already been loaded, e.g. fetched over the network or decompressed in memory.
+    ///
+    /// # Errors
+    /// A malformed line is reported as a [ParseError] carrying its line number.
+    pub fn parse_from_str(data: &str) -> Result<Self, ParseError> {
+        let mut pci_ids = Self::new();
+        pci_ids.parse_lines(data, false, false, ParseOptions::default())?;
+
+        Ok(pci_ids)
+    }
+
+    /// Parse the raw contents of a pci.ids file like [PciIds::parse_from_str], replacing invalid
+    /// UTF-8, e.g. from a corrupted download, with `U+FFFD`.
+    ///
+    /// Names containing the invalid bytes end up with replacement characters, while an id
+    /// containing them fails to parse like any other malformed id.
+    ///
+    /// # Errors
+    /// A malformed line is reported as a [ParseError] carrying its line number.
+    pub fn parse_from_bytes(data: &[u8]) -> Result<Self, ParseError> {
+        Self::parse_bytes_with_options(data, ParseOptions::default())
+    }
+
+    /// Parse the raw contents of a pci.ids file with the given [ParseOptions], see
+    /// [PciIds::parse_from_bytes].
+    ///
+    /// # Errors
+    /// With [ParseOptions::lossy_utf8] turned off, invalid UTF-8 is reported as
+    /// [ParseError::InvalidUtf8] with the offset of the first invalid byte.
+    pub fn parse_bytes_with_options(
+        data: &[u8],
+        options: ParseOptions,
+    ) -> Result<Self, ParseError> {
+        let text = decode_utf8(data, options.lossy_utf8)?;
+        Self::parse_with_options(&text, options)
+    }
+
+    /// Parse the [Vendor]s from the contents of a pci.ids file one at a time, calling `f` with
+    /// each vendor once all of its devices are parsed, instead of collecting them into a
+    /// [PciIds].
+    ///
+    /// Only the vendor being parsed is kept in memory. Returning [ControlFlow::Break] from `f`
+    /// stops parsing right there, so finding a single vendor doesn't parse the rest of the file.
+    /// The [Class]es are skipped.
+    ///
+    /// ```
+    /// use core::ops::ControlFlow;
+    /// use pci_id::pci_ids::PciIds;
+    ///
+    /// let data = "0e11  Compaq Computer Corporation\n\
+    ///             \t0046  Smart Array 64xx\n\
+    ///             10de  NVIDIA Corporation\n";
+    /// let mut seen = Vec::new();
+    /// let mut compaq = None;
+    /// PciIds::for_each_vendor(data, |vendor| {
+    ///     seen.push(vendor.id());
+    ///     if vendor.id() == 0x0e11 {
+    ///         compaq = Some(vendor.clone());
+    ///         return ControlFlow::Break(());
+    ///     }
+    ///     ControlFlow::Continue(())
+    /// })
+    /// .unwrap();
+    /// assert_eq!(compaq.unwrap().name(), "Compaq Computer Corporation");
+    /// // NVIDIA comes after the break and is never handed to `f`
+    /// assert_eq!(seen, [0x0e11]);
+    /// ```
+    ///
+    /// # Errors
+    /// A malformed line is reported as a [ParseError] carrying its line number, as long as it
+    /// comes before the point where parsing stopped.
+    pub fn for_each_vendor(
+        data: &str,
+        mut f: impl FnMut(&Vendor) -> ControlFlow<()>,
+    ) -> Result<(), ParseError> {
+        let mut classifier = Classifier::default();
+        let mut builder = TreeBuilder::new(ParseOptions::default());
+        for (index, line) in data.lines().enumerate() {
+            let line_number = index + 1;
+            if line.starts_with("C ") {
+                break;
+            }
+            if let Some(entry) = classifier.classify(line_number, line)? {
+                // A new vendor means the one before it is complete
+                if matches!(entry, Entry::Vendor { .. }) {
+                    if let Some(vendor) = builder.take_vendor() {
+                        if f(&vendor).is_break() {
+                            return Ok(());
+                        }
+                    }
+                }
+                builder.push(line_number, entry)?;
+            }
+        }
+        if let Some(vendor) = builder.take_vendor() {
+            let _ = f(&vendor);
+        }
+        Ok(())
+    }
+
+    /// Parse the snapshot of pci.ids embedded in the crate, for systems that don't ship the file,
+    /// like containers, Windows or macOS.
+    ///
+    /// # Note
+    /// The snapshot is only as recent as the version of this crate, so it can be missing newer
+    /// hardware. Prefer the file of the system where there is one.
+    ///
+    /// # Errors
+    /// Can't fail for the snapshot shipped with the crate, the [Result] is kept for parity with
+    /// the other parse functions.
+    #[cfg(feature = "bundled")]
+    pub fn from_bundled() -> Result<Self, ParseError> {
+        Self::parse_from_str(BUNDLED_PCI_IDS)
+    }
+
+    /// Parse a pci.ids file line by line from the given reader, without reading it into memory as
+    /// a whole first.
+    ///
+    /// This allows streaming in a decompressed file, e.g. through a `flate2::read::GzDecoder`
+    /// wrapped in a [std::io::BufReader].
+    ///
+    /// Invalid UTF-8 is replaced like with [PciIds::parse_from_bytes].
+    ///
+    /// # Errors
+    /// Fails if reading from the reader fails or the contents are malformed.
+    #[cfg(feature = "std")]
+    pub fn parse_from_reader<R: BufRead>(reader: R) -> Result<Self, ParseError> {
+        let mut classifier = Classifier::default();
+        let mut builder = TreeBuilder::new(ParseOptions::default());
+
+        for (index, line) in reader.split(b'\n').enumerate() {
+            let line = line?;
+            let line = line.strip_suffix(b"\r").unwrap_or(&line);
+            let line = String::from_utf8_lossy(line);
+            let line_number = index + 1;
+            if let Some(entry) = classifier.classify(line_number, &line)? {
+                builder.push(line_number, entry)?;
+            }
+        }
+
+        let mut pci_ids = Self::new();
+        pci_ids.append(builder);
+        Ok(pci_ids)
+    }
+
+    /// Parse a pci.ids file that may be gzip-compressed, like the `pci.ids.gz` some distributions
+    /// ship instead of the plain file.
+    ///
+    /// Compression is detected from the contents rather than the extension, so plain files are
+    /// accepted as well.
+    ///
+    /// # Errors
+    /// Fails if the file can't be read or decompressed, or its contents are malformed.
+    #[cfg(feature = "gzip")]
+    pub fn parse_gz_path(path: &Path) -> Result<Self, ParseError> {
+        const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+        let mut reader = io::BufReader::new(std::fs::File::open(path)?);
+        if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+            Self::parse_from_reader(io::BufReader::new(flate2::read::GzDecoder::new(reader)))
+        } else {
+            Self::parse_from_reader(reader)
+        }
+    }
+
+    /// Parse a pci.ids file by memory mapping it, instead of reading it into a [String] first.
+    ///
+    /// Meant for very large custom files, where the copy of the whole file would be significant.
+    /// The names are still copied out of the mapping, so the result doesn't borrow the file.
+    ///
+    /// # Note
+    /// The file must not be modified while it is being parsed, e.g. by an update running at the
+    /// same time. Replacing it by renaming a new file over it is fine.
+    ///
+    /// Invalid UTF-8 is replaced like with [PciIds::parse_from_bytes], which copies the file only
+    /// in that case.
+    ///
+    /// # Errors
+    /// Fails if the file can't be mapped or its contents are malformed.
+    #[cfg(feature = "mmap")]
+    #[allow(unsafe_code)]
+    pub fn parse_mmap(path: &Path) -> Result<Self, ParseError> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the mapping is only read within this function and dropped before returning,
+        // concurrent modification is excluded by the documented precondition.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Self::parse_from_bytes(&map)
+    }
+
+    /// Parse the given contents of a pci.ids file like [PciIds::parse_from_str], but split into
+    /// chunks that are parsed on the rayon thread pool.
+    ///
+    /// The vendor section is only split right before vendor lines that don't have a comment above
+    /// them, as every other line depends on the lines before it. The class section is small and
+    /// stays in one piece. The result, including the line number of an error, is the same as that
+    /// of [PciIds::parse_from_str], this only pays off for files of several megabytes.
+    ///
+    /// # Errors
+    /// A malformed line is reported as a [ParseError] carrying its line number. If there are
+    /// several, the first one in the file is reported.
+    #[cfg(feature = "parallel")]
+    pub fn parse_from_str_parallel(data: &str) -> Result<Self, ParseError> {
+        Self::parse_chunks(data, rayon::current_num_threads() * 4)
+    }
+
+    /// Parse the contents in about `count` chunks, see [PciIds::parse_from_str_parallel].
+    #[cfg(feature = "parallel")]
+    fn parse_chunks(data: &str, count: usize) -> Result<Self, ParseError> {
+        use rayon::prelude::*;
+
+        let builders: Vec<Result<TreeBuilder, ParseError>> = split_at_vendors(data, count)
+            .into_par_iter()
+            .map(|(first_line_number, chunk)| {
+                let mut classifier = Classifier::default();
+                let mut builder = TreeBuilder::new(ParseOptions::default());
+                for (index, line) in chunk.lines().enumerate() {
+                    let line_number = first_line_number + index;
+                    if let Some(entry) = classifier.classify(line_number, line)? {
+                        builder.push(line_number, entry)?;
+                    }
+                }
+                Ok(builder)
+            })
+            .collect();
+
+        let mut pci_ids = Self::new();
+        for builder in builders {
+            pci_ids.append(builder?);
+        }
+        Ok(pci_ids)
+    }
+
+    /// Parse the pci.ids file at `source` like [PciIds::parse_pci_id_list], keeping a binary copy
+    /// of the result at `cache` so that later calls can skip parsing.
+    ///
+    /// # Cache invalidation
+    /// The cache records the modification time of `source` and the version of this crate it was
+    /// written with. It is only used while both match exactly; if either changed, or the cache is
+    /// missing or unreadable, `source` is parsed again and the cache rewritten. Changing `source`
+    /// while keeping its modification time goes unnoticed.
+    ///
+    /// Writing the cache is best effort, failing to do so doesn't fail the load.
+    ///
+    /// # Errors
+    /// Fails if `source` can't be read or its contents are malformed.
+    #[cfg(feature = "bincode")]
+    pub fn load_cached(source: &Path, cache: &Path) -> Result<Self, ParseError> {
+        const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+        let modified = std::fs::metadata(source)?.modified()?;
+        if let Ok(data) = std::fs::read(cache) {
+            if let Ok((version, cached_modified, pci_ids)) =
+                bincode::deserialize::<(String, std::time::SystemTime, Self)>(&data)
+            {
+                if version == VERSION && cached_modified == modified {
+                    return Ok(pci_ids);
+                }
+            }
+        }
+
+        let pci_ids = Self::parse_pci_id_list(source)?;
+        if let Ok(data) = bincode::serialize(&(VERSION, modified, &pci_ids)) {
+            let _ = std::fs::write(cache, data);
+        }
+        Ok(pci_ids)
+    }
+
+    /// Serialize `self` into an rkyv archive, which [PciIds::from_archived_bytes] can read
+    /// without deserializing, e.g. straight from a memory mapped file.
+    ///
+    /// # Format
+    /// The archive is not a stable format. It depends on the version of this crate, as the layout
+    /// follows the private fields of the types, and on the version and the format features of
+    /// rkyv, like its endianness and pointer width. Archives should be written by the same build
+    /// that reads them, or at least be rebuilt whenever either crate is updated.
+    ///
+    /// # Errors
+    /// Fails if serializing runs out of memory or an entry is too large for the archive.
+    #[cfg(feature = "rkyv")]
+    pub fn archive_to_bytes(&self) -> Result<rkyv::util::AlignedVec, rkyv::rancor::Error> {
+        rkyv::to_bytes(self)
+    }
+
+    /// Access an archive written by [PciIds::archive_to_bytes] in place, without copying or
+    /// deserializing any entry. [rkyv::deserialize] turns the result into a [PciIds] if needed.
+    ///
+    /// The whole archive is validated first, so malformed or truncated bytes are rejected instead
+    /// of causing undefined behavior; lookups on the result are then as cheap as on a [PciIds].
+    /// The caveats about the format of [PciIds::archive_to_bytes] apply, an archive from another
+    /// version may be rejected or, if its layout happens to validate, read as different entries.
+    ///
+    /// # Errors
+    /// Fails if `bytes` are not a valid archive, or are not aligned like the start of the
+    /// [AlignedVec](rkyv::util::AlignedVec) they were written to. A memory map is aligned, a
+    /// [Vec] of bytes possibly isn't.
+    #[cfg(feature = "rkyv")]
+    pub fn from_archived_bytes(bytes: &[u8]) -> Result<&ArchivedPciIds, rkyv::rancor::Error> {
+        rkyv::access::<ArchivedPciIds, rkyv::rancor::Error>(bytes)
+    }
+
+    /// Parse the pci.ids file kept at `cache`, replacing it with the latest version from
+    /// [DEFAULT_URL](crate::update::DEFAULT_URL) first if it is older than `max_age` or missing.
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    /// use std::time::Duration;
+    /// use pci_id::pci_ids::PciIds;
+    ///
+    /// let week = Duration::from_secs(7 * 24 * 60 * 60);
+    /// let pci_ids = PciIds::load_or_update(Path::new("/var/cache/pci.ids"), week).unwrap();
+    /// ```
+    ///
+    /// A cache that is recent enough but can't be read or parsed is downloaded again as well. A
+    /// download that doesn't parse is not stored.
+    ///
+    /// # Errors
+    /// Fails if downloading, storing or parsing the new file fails. A stale cache is not used in
+    /// place of a failed download.
+    #[cfg(feature = "online")]
+    pub fn load_or_update(cache: &Path, max_age: Duration) -> Result<Self, UpdateError> {
+        crate::update::load_or_update(None, cache, max_age)
+    }
+
+    /// Parse only the ids and their hierarchy from the contents of a pci.ids file, skipping every
+    /// name.
+    ///
+    /// This avoids allocating a [String] per entry and is meant for when only the existence of ids
+    /// matters.
+    pub fn parse_ids_only(data: &str) -> Result<IdTopology, ParseError> {
+        IdTopology::parse(data)
+    }
+
+    /// Parse every file in the given directory, e.g. `/etc/pci.ids.d/`, and merge them into one
+    /// [PciIds] instance.
+    ///
+    /// The files are parsed in the order of their names, and each one is merged on top of the ones
+    /// before it, so a later file overrides the names of entries with the same id. Subdirectories
+    /// are skipped.
+    ///
+    /// # Errors
+    /// Fails if the directory or any of its files can not be read, or if any file is malformed.
+    #[cfg(feature = "std")]
+    pub fn parse_dir(dir: &Path) -> Result<Self, ParseError> {
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                paths.push(entry.path());
+            }
+        }
+        paths.sort();
+
+        let mut pci_ids = Self::new();
+        for path in paths {
+            let data = std::fs::read(path)?;
+            pci_ids.merge(Self::parse_from_bytes(&data)?);
+        }
+
+        Ok(pci_ids)
+    }
+
+    /// Merge `other` into `self`, e.g. to layer a small overlay of extra ids on top of the system
+    /// file.
+    ///
+    /// # Override semantics
+    /// - Vendors, devices, classes and subclasses are matched by id. A match takes the name of the
+    ///   entry from `other`, as well as its comment if it has one, and its children are merged
+    ///   the same way. If `self` has an id more than once, only the first entry is merged into.
+    /// - Subdevices, matched by both of their ids, and interfaces are replaced as a whole.
+    /// - Entries without a match are appended after the existing ones, so the lists may no longer
+    ///   be sorted. Use [PciIds::sort] to restore the order.
+    /// - Nothing is ever removed.
+    /// - The version and date of `other` win if it has them, and its orphan subdevices are added.
+    pub fn merge(&mut self, other: PciIds) {
+        for vendor in other.vendors {
+            match self.vendors.iter_mut().find(|v| v.id() == vendor.id()) {
+                Some(v) => v.merge(vendor),
+                None => self.vendors.push(vendor),
+            }
+        }
+        for class in other.classes {
+            match self.classes.iter_mut().find(|c| c.class() == class.class()) {
+                Some(c) => c.merge(class),
+                None => self.classes.push(class),
+            }
+        }
+        self.orphan_subdevices.extend(other.orphan_subdevices);
+        self.version = other.version.or(self.version.take());
+        self.date = other.date.or(self.date.take());
+    }
+
+    /// Parse the given contents of a pci.ids file to a [PciIds] instance while holding on to the
+    /// source text.
+    ///
+    /// The parsed data is identical to the one produced by [PciIds::parse_pci_id_list], the
+    /// buffer is merely kept and can be accessed through [PciIds::raw_source].
+    pub fn parse_retaining_source(data: String) -> Result<Self, ParseError> {
+        let mut pci_ids = Self::new();
+        pci_ids.parse_lines(&data, false, false, ParseOptions::default())?;
+        pci_ids.source = Some(data.into_boxed_str());
+
+        Ok(pci_ids)
+    }
+
+    /// Parse the given contents of a pci.ids file to a [PciIds] instance with the given
+    /// [ParseOptions].
+    pub fn parse_with_options(data: &str, options: ParseOptions) -> Result<Self, ParseError> {
+        let mut pci_ids = Self::new();
+        pci_ids.parse_lines(data, false, false, options)?;
+
+        Ok(pci_ids)
+    }
+
+    /// Parse the given contents of a pci.ids file to a [PciIds] instance, collecting everything
+    /// that looks off instead of failing.
+    ///
+    /// Duplicate and out of order ids are reported but kept. Malformed lines are reported and
+    /// skipped, and so are the lines nested below them, which would otherwise end up attached to
+    /// the wrong parent.
+    pub fn parse_with_diagnostics(data: &str) -> (Self, Vec<Diagnostic>) {
+        let mut classifier = Classifier::default();
+        let mut builder = TreeBuilder::new(ParseOptions::default());
+        let mut checker = OrderChecker::default();
+        let mut diagnostics = Vec::new();
+        let mut skipped_depth = None;
+
+        for (index, line) in data.lines().enumerate() {
+            let line_number = index + 1;
+            let depth = line.bytes().take_while(|&b| b == b'\t').count();
+            if skipped_depth.is_some_and(|skipped| depth > skipped) && !line.trim().is_empty() {
+                let error = ParseError::UnexpectedIndent { line_number };
+                diagnostics.push(Diagnostic::Skipped(error));
+                continue;
+            }
+            skipped_depth = None;
+
+            let result = classifier
+                .classify(line_number, line)
+                .and_then(|entry| match entry {
+                    Some(entry) => builder.push(line_number, entry).map(|()| Some(entry)),
+                    None => Ok(None),
+                });
+            match result {
+                Ok(Some(entry)) => diagnostics.extend(checker.check(line_number, &entry)),
+                Ok(None) => {}
+                Err(error) => {
+                    diagnostics.push(Diagnostic::Skipped(error));
+                    skipped_depth = Some(depth);
+                }
+            }
+        }
+
+        let mut pci_ids = Self::new();
+        pci_ids.append(builder);
+        (pci_ids, diagnostics)
+    }
+
+    /// Parse the given contents of a pci.ids file to a [PciIds] instance, reporting how far along
+    /// the parsing is.
+    ///
+    /// The callback receives the number of lines processed so far and the total number of lines.
+    /// It is called at the start, every few thousand lines, and once more with both numbers equal
+    /// when parsing has finished.
+    pub fn parse_with_progress<F: FnMut(usize, usize)>(
+        data: &str,
+        progress: F,
+    ) -> Result<Self, ParseError> {
+        let mut pci_ids = Self::new();
+        pci_ids.parse_lines_with_progress(
+            data,
+            Classifier::default(),
+            false,
+            false,
+            ParseOptions::default(),
+            (data.lines().count(), progress),
+        )?;
+
+        Ok(pci_ids)
+    }
+
+    /// Parse the contents of a pci.ids-like file with a different layout, using the given
+    /// [LineClassifier] to make sense of its lines instead of the canonical [Classifier].
+    ///
+    /// # Errors
+    /// Fails if the classifier rejects a line, or the entries it returns do not nest properly.
+    pub fn parse_with_classifier<C: LineClassifier>(
+        data: &str,
+        classifier: C,
+    ) -> Result<Self, ParseError> {
+        let mut pci_ids = Self::new();
+        pci_ids.parse_lines_with_progress(
+            data,
+            classifier,
+            false,
+            false,
+            ParseOptions::default(),
+            (0, |_, _| {}),
+        )?;
+
+        Ok(pci_ids)
+    }
+}
+
+impl Default for PciIds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse the contents of a pci.ids file, like [PciIds::parse_from_str].
+///
+/// ```
+/// use pci_id::pci_ids::PciIds;
+///
+/// let data = String::from("0e11  Compaq Computer Corporation\n\t0046  Smart Array 64xx\n");
+/// let pci_ids: PciIds = data.as_str().try_into().unwrap();
+/// assert_eq!(pci_ids.vendor_by_id(0x0e11).unwrap().devices().len(), 1);
+/// ```
+impl TryFrom<&str> for PciIds {
+    type Error = ParseError;
+
+    fn try_from(data: &str) -> Result<Self, Self::Error> {
+        Self::parse_from_str(data)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedPciIds {
+    /// Vendors of the archive, in file order.
+    pub fn vendors(&self) -> &[ArchivedVendor] {
+        &self.vendors
+    }
+
+    /// Classes of the archive, in file order.
+    pub fn classes(&self) -> &[ArchivedClass] {
+        &self.classes
+    }
+
+    /// Returns the first vendor with the given id, like [PciIds::vendor_by_id].
+    pub fn vendor_by_id(&self, id: impl Into<VendorId>) -> Option<&ArchivedVendor> {
+        let VendorId(id) = id.into();
+        self.vendors.iter().find(|v| v.id() == id)
+    }
+
+    /// Returns the class with the given id, like [PciIds::class_by_id].
+    pub fn class_by_id(&self, id: impl Into<ClassId>) -> Option<&ArchivedClass> {
+        let ClassId(id) = id.into();
+        self.classes.iter().find(|c| c.id() == id)
+    }
+}
+
+/// Iterate over the vendors, like [PciIds::iter_vendors].
+impl<'a> IntoIterator for &'a PciIds {
+    type Item = &'a Vendor;
+    type IntoIter = core::slice::Iter<'a, Vendor>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.vendors.iter()
+    }
+}
+
+/// Flat table of device names keyed by `(vendor id, device id)`. If an id occurs more than once
+/// the first entry wins, just like with [Vendor::device_by_id].
+#[cfg(feature = "std")]
+impl From<&PciIds> for HashMap<(u16, u16), String> {
+    fn from(pci_ids: &PciIds) -> Self {
+        let mut names = HashMap::with_capacity(pci_ids.device_count());
+        for (vendor, device) in pci_ids.all_devices() {
+            names
+                .entry((vendor.id(), device.id()))
+                .or_insert_with(|| device.name().to_owned());
+        }
+        names
+    }
+}
+
+/// Flat table of vendor names keyed by vendor id. If an id occurs more than once the first entry
+/// wins, just like with [PciIds::vendor_by_id].
+#[cfg(feature = "std")]
+impl From<&PciIds> for HashMap<u16, String> {
+    fn from(pci_ids: &PciIds) -> Self {
+        let mut names = HashMap::with_capacity(pci_ids.vendor_count());
+        for vendor in pci_ids.iter_vendors() {
+            names
+                .entry(vendor.id())
+                .or_insert_with(|| vendor.name().to_owned());
+        }
+        names
+    }
+}
+
+/// A single entry of a pci.ids file, borrowing its name from the line it was read from.
+///
+/// Produced line by line by a [LineClassifier].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Entry<'a> {
+    /// Text of a comment line, without the leading `#`.
+    Comment(&'a str),
+    /// A vendor line, at the top level of the vendor section.
+    Vendor {
+        /// Id of the vendor.
+        id: u16,
+        /// Name of the vendor.
+        name: &'a str,
+    },
+    /// A device line, below a vendor.
+    Device {
+        /// Id of the device.
+        id: u16,
+        /// Name of the device.
+        name: &'a str,
+    },
+    /// A subdevice line, below a device.
+    SubDevice {
+        /// Id of the subvendor.
+        subvendor_id: u16,
+        /// Id of the subdevice.
+        subdevice_id: u16,
+        /// Name of the subdevice.
+        name: &'a str,
+    },
+    /// A class line, starting with `C `.
+    Class {
+        /// Id of the class.
+        id: u8,
+        /// Name of the class.
+        name: &'a str,
+    },
+    /// A subclass line, below a class.
+    SubClass {
+        /// Id of the subclass.
+        id: u8,
+        /// Name of the subclass.
+        name: &'a str,
+    },
+    /// A programming interface line, below a subclass.
+    Interface {
+        /// Id of the programming interface.
+        id: u8,
+        /// Name of the programming interface.
+        name: &'a str,
+    },
+}
+
+/// Ids seen so far among the children of one parent, for spotting duplicates and misordering.
+#[derive(Debug)]
+struct Siblings<K> {
+    first_lines: BTreeMap<K, usize>,
+    previous: Option<(K, usize)>,
+}
+
+impl<K> Default for Siblings<K> {
+    fn default() -> Self {
+        Self {
+            first_lines: BTreeMap::new(),
+            previous: None,
+        }
+    }
+}
+
+impl<K: Ord + Copy> Siblings<K> {
+    fn check(&mut self, line_number: usize, id: K) -> Option<Diagnostic> {
+        let previous = self.previous.replace((id, line_number));
+        if let Some(&first_line_number) = self.first_lines.get(&id) {
+            return Some(Diagnostic::Duplicate {
+                line_number,
+                first_line_number,
+            });
+        }
+        self.first_lines.insert(id, line_number);
+        match previous {
+            Some((previous_id, previous_line_number)) if previous_id > id => {
+                Some(Diagnostic::OutOfOrder {
+                    line_number,
+                    previous_line_number,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Checks the ids of [Entry]s against their siblings, for [PciIds::parse_with_diagnostics].
+#[derive(Debug, Default)]
+struct OrderChecker {
+    vendors: Siblings<u16>,
+    devices: Siblings<u16>,
+    subdevices: Siblings<(u16, u16)>,
+    classes: Siblings<u8>,
+    subclasses: Siblings<u8>,
+    interfaces: Siblings<u8>,
+}
+
+impl OrderChecker {
+    fn check(&mut self, line_number: usize, entry: &Entry) -> Option<Diagnostic> {
+        match *entry {
+            Entry::Comment(_) => None,
+            Entry::Vendor { id, .. } => {
+                self.devices = Siblings::default();
+                self.subdevices = Siblings::default();
+                self.vendors.check(line_number, id)
+            }
+            Entry::Device { id, .. } => {
+                self.subdevices = Siblings::default();
+                self.devices.check(line_number, id)
+            }
+            Entry::SubDevice {
+                subvendor_id,
+                subdevice_id,
+                ..
+            } => self
+                .subdevices
+                .check(line_number, (subvendor_id, subdevice_id)),
+            Entry::Class { id, .. } => {
+                self.subclasses = Siblings::default();
+                self.interfaces = Siblings::default();
+                self.classes.check(line_number, id)
+            }
+            Entry::SubClass { id, .. } => {
+                self.interfaces = Siblings::default();
+                self.subclasses.check(line_number, id)
+            }
+            Entry::Interface { id, .. } => self.interfaces.check(line_number, id),
+        }
+    }
+}
+
+/// Turns the lines of a pci.ids file into [Entry]s, for [PciIds::parse_with_classifier].
+///
+/// [Classifier] understands the canonical format. Files nesting their entries differently can
+/// be read by working out the depth of each line and handing the rest of it to
+/// [Classifier::classify_at_depth], which knows what the entries on each level look like:
+/// ```
+/// use pci_id::pci_ids::{Classifier, Entry, LineClassifier, ParseError, PciIds};
+///
+/// /// Reads files indented with four spaces per level instead of tabs.
+/// #[derive(Default)]
+/// struct FourSpaces(Classifier);
+///
+/// impl LineClassifier for FourSpaces {
+///     fn classify<'a>(
+///         &mut self,
+///         line_number: usize,
+///         line: &'a str,
+///     ) -> Result<Option<Entry<'a>>, ParseError> {
+///         let content = line.trim_start_matches(' ');
+///         let depth = (line.len() - content.len()) / 4;
+///         self.0.classify_at_depth(line_number, depth, content)
+///     }
+/// }
+///
+/// let pci_ids = PciIds::parse_with_classifier(
+///     "0e11  Compaq Computer Corporation\n    0046  Smart Array 64xx\n",
+///     FourSpaces::default(),
+/// )
+/// .unwrap();
+/// let compaq = pci_ids.vendor_by_id(0x0e11).unwrap();
+/// assert_eq!(compaq.device_by_id(0x0046).unwrap().name(), "Smart Array 64xx");
+/// ```
+pub trait LineClassifier {
+    /// Classify a single line, returning `None` for empty lines and lines that aren't understood.
+    ///
+    /// # Errors
+    /// Fails if the line is malformed, the error carrying `line_number`.
+    fn classify<'a>(
+        &mut self,
+        line_number: usize,
+        line: &'a str,
+    ) -> Result<Option<Entry<'a>>, ParseError>;
+}
+
+/// [LineClassifier] for the canonical pci.ids format, which indents entries with tabs.
+///
+/// Indented lines mean different things in the vendor and the class section, so the classifier has
+/// to remember which section it is in. It starts out in the vendor section and switches to the
+/// class section for good with the first `C ` line, as the class section runs to the end of the
+/// file.
+#[derive(Debug, Default)]
+pub struct Classifier {
+    in_class_section: bool,
+}
+
+impl Classifier {
+    /// Classify the `content` of a line that is nested `depth` levels deep, with its indentation
+    /// already removed. Comments and empty lines are only recognized at the top level.
+    ///
+    /// # Errors
+    /// Fails if the content is malformed or nested deeper than the two levels the format allows,
+    /// the error carrying `line_number`.
+    pub fn classify_at_depth<'a>(
+        &mut self,
+        line_number: usize,
+        depth: usize,
+        content: &'a str,
+    ) -> Result<Option<Entry<'a>>, ParseError> {
+        if depth == 0 {
+            if let Some(comment) = content.strip_prefix('#') {
+                return Ok(Some(Entry::Comment(comment.trim())));
+            }
+            // Skip empty lines
+            if content.is_empty() {
+                return Ok(None);
+            }
+        }
+
+        // Both sections nest at most two levels below their top-level entries
+        if depth > 2 {
+            return Err(ParseError::TooDeeplyNested { line_number, depth });
+        }
+
+        let missing_separator = || ParseError::MissingSeparator { line_number };
+        let invalid_hex = |source| ParseError::InvalidHex {
+            line_number,
+            source,
+        };
+        let hex_u16 = |id: &str| u16::from_str_radix(id.trim(), 16).map_err(invalid_hex);
+        let hex_u8 = |id: &str| u8::from_str_radix(id.trim(), 16).map_err(invalid_hex);
+
+        // A class line is a literal `C ` followed by the class id. Checking for the space as well
+        // keeps vendor ids starting with an uppercase `C` from being taken for a class.
+        let entry = if depth == 0 && content.starts_with("C ") {
+            self.in_class_section = true;
+            let ([_, id], name) = split_ids(content).ok_or_else(missing_separator)?;
+            Entry::Class {
+                id: hex_u8(id)?,
+                name,
+            }
+
+        // Vendor section: vendors at the top level, their devices one tab in and the subdevices of
+        // those two tabs in
+        } else if !self.in_class_section {
+            match depth {
+                0 if content.starts_with(|c: char| c.is_ascii_hexdigit()) => {
+                    let ([id], name) = split_ids(content).ok_or_else(missing_separator)?;
+                    Entry::Vendor {
+                        id: hex_u16(id)?,
+                        name,
+                    }
+                }
+                0 => return Ok(None),
+                1 => {
+                    let ([id], name) = split_ids(content).ok_or_else(missing_separator)?;
+                    Entry::Device {
+                        id: hex_u16(id)?,
+                        name,
+                    }
+                }
+                _ => {
+                    let ([subvendor_id, subdevice_id], name) =
+                        split_ids(content).ok_or_else(missing_separator)?;
+                    Entry::SubDevice {
+                        subvendor_id: hex_u16(subvendor_id)?,
+                        subdevice_id: hex_u16(subdevice_id)?,
+                        name,
+                    }
+                }
+            }
+
+        // Class section: everything but a class has to be indented, subclasses one tab in and
+        // their programming interfaces two tabs in
+        } else {
+            let ([id], name) = split_ids(content).ok_or_else(missing_separator)?;
+            let id = hex_u8(id)?;
+            match depth {
+                0 => return Err(ParseError::UnexpectedIndent { line_number }),
+                1 => Entry::SubClass { id, name },
+                _ => Entry::Interface { id, name },
+            }
+        };
+        Ok(Some(entry))
+    }
+}
+
+impl LineClassifier for Classifier {
+    #[inline(always)]
+    fn classify<'a>(
+        &mut self,
+        line_number: usize,
+        line: &'a str,
+    ) -> Result<Option<Entry<'a>>, ParseError> {
+        // Entries are indented with tabs only
+        if line.starts_with(' ') {
+            return Err(ParseError::UnexpectedIndent { line_number });
+        }
+        let content = line.trim_start_matches('\t');
+        self.classify_at_depth(line_number, line.len() - content.len(), content)
+    }
+}
+
+/// Decode the raw contents of a file, replacing invalid UTF-8 if `lossy` or failing on it
+/// otherwise. Valid contents aren't copied.
+fn decode_utf8(data: &[u8], lossy: bool) -> Result<Cow<'_, str>, ParseError> {
+    if lossy {
+        Ok(String::from_utf8_lossy(data))
+    } else {
+        core::str::from_utf8(data)
+            .map(Cow::Borrowed)
+            .map_err(|source| ParseError::invalid_utf8(data, source))
+    }
+}
+
+/// Split the name of an entry from a trailing comment, which is introduced by two spaces or a tab
+/// followed by `#`. A `#` within the name, like in `Controller #1`, is left alone.
+pub(crate) fn split_inline_comment(name: &str) -> (&str, Option<&str>) {
+    let start = name
+        .match_indices('#')
+        .map(|(index, _)| index)
+        .find(|&index| name[..index].ends_with("  ") || name[..index].ends_with('\t'));
+    match start {
+        Some(index) => (name[..index].trim_end(), Some(name[index + 1..].trim())),
+        None => (name, None),
+    }
+}
+
+/// Estimate the number of vendors and classes in a pci.ids file of `len` bytes.
+///
+/// The upstream file has a vendor per 600 bytes or so, the estimate errs on the side of a few
+/// vendors too many. There are only a couple dozen classes, but a small file has even fewer.
+fn estimate_capacity(len: usize) -> (usize, usize) {
+    const BYTES_PER_VENDOR: usize = 512;
+    const MAX_CLASSES: usize = 32;
+
+    let vendors = len / BYTES_PER_VENDOR;
+    (vendors, vendors.min(MAX_CLASSES))
+}
+
+/// Split a line into its `N` ids and its name, dropping the leading tabs.
+///
+/// Ids never contain whitespace, so they are separated from each other by any amount of spaces and
+/// tabs, e.g. the two ids of a subdevice. The name can contain single spaces though, so the last id
+/// has to be followed by two spaces or, as some third-party files do, a tab.
+fn split_ids<const N: usize>(line: &str) -> Option<([&str; N], &str)> {
+    let is_blank = |c: char| c == ' ' || c == '\t';
+    let mut rest = line.trim_start_matches('\t');
+    let mut ids = [""; N];
+    for id in &mut ids {
+        rest = rest.trim_start_matches(is_blank);
+        let end = rest.find(is_blank)?;
+        *id = &rest[..end];
+        rest = &rest[end..];
+    }
+    let separator = &rest[..rest.len() - rest.trim_start_matches(is_blank).len()];
+    (separator.len() >= 2 || separator.contains('\t')).then_some((ids, rest.trim()))
+}
+
+/// Split the contents of a pci.ids file into about `count` chunks of similar size, each along with
+/// the number of its first line.
+///
+/// Every chunk but the first starts with a vendor line that has no comment right above it, so each
+/// chunk parses to the same entries it would as part of the whole file. The class section is never
+/// split.
+#[cfg(feature = "parallel")]
+fn split_at_vendors(data: &str, count: usize) -> Vec<(usize, &str)> {
+    let chunk_size = data.len() / count.max(1);
+    let mut chunks = Vec::new();
+    let (mut start, mut start_line_number) = (0, 1);
+    let (mut offset, mut line_number) = (0, 1);
+    let mut after_comment = false;
+    for line in data.split_inclusive('\n') {
+        if line.starts_with("C ") {
+            break;
+        }
+        let is_vendor = line.starts_with(|c: char| c.is_ascii_hexdigit());
+        if is_vendor && !after_comment && offset - start > chunk_size {
+            chunks.push((start_line_number, &data[start..offset]));
+            (start, start_line_number) = (offset, line_number);
+        }
+        after_comment = line.starts_with('#');
+        offset += line.len();
+        line_number += 1;
+    }
+    chunks.push((start_line_number, &data[start..]));
+    chunks
+}
+
+/// Assembles [Entry]s into the tree of vendors and classes.
+///
+/// Children are collected until their parent is complete, which is when the next entry on the same
+/// or a higher level shows up, or the input ends.
+#[derive(Debug, Default)]
+struct TreeBuilder {
+    options: ParseOptions,
+    vendors: Vec<Vendor>,
+    devices: Vec<Device>,
+    subdevices: Vec<SubDevice>,
+    classes: Vec<Class>,
+    subclasses: Vec<SubClass>,
+    interfaces: Vec<Interface>,
+    orphan_subdevices: Vec<SubDevice>,
+    version: Option<String>,
+    date: Option<String>,
+    /// Run of comment lines along with the line number of the last one.
+    comment: Option<(String, usize)>,
+}
+
+impl TreeBuilder {
+    fn new(options: ParseOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    fn push(&mut self, line_number: usize, entry: Entry<'_>) -> Result<(), ParseError> {
+        // A comment only belongs to an entry directly below it
+        let comment = match self.comment.take() {
+            Some((comment, last)) if last + 1 == line_number => Some(comment),
+            _ => None,
+        };
+        match entry {
+            Entry::Comment(text) => {
+                // The header comes before the first entry
+                if self.vendors.is_empty() && self.classes.is_empty() {
+                    if let Some(version) = text.strip_prefix("Version:") {
+                        self.version
+                            .get_or_insert_with(|| version.trim().to_owned());
+                    } else if let Some(date) = text.strip_prefix("Date:") {
+                        self.date.get_or_insert_with(|| date.trim().to_owned());
+                    }
+                }
+                if self.options.keep_comments {
+                    let comment = match comment {
+                        Some(comment) => comment + "\n" + text,
+                        None => text.to_owned(),
+                    };
+                    self.comment = Some((comment, line_number));
+                }
+            }
+            Entry::Vendor { id, name } => {
+                self.finish_vendor();
+                let (name, comment) = self.split_comment(name, comment);
+                let mut vendor = Vendor::new(id, name.to_owned());
+                vendor.set_comment(comment);
+                self.vendors.push(vendor);
+            }
+            Entry::Device { id, name } => {
+                if self.vendors.is_empty() {
+                    return Err(ParseError::UnexpectedIndent { line_number });
+                }
+                self.finish_device();
+                let (name, comment) = self.split_comment(name, comment);
+                let mut device = Device::new(id, name.to_owned());
+                device.set_comment(comment);
+                self.devices.push(device);
+            }
+            Entry::SubDevice {
+                subvendor_id,
+                subdevice_id,
+                name,
+            } => {
+                let (name, comment) = self.split_comment(name, comment);
+                let mut subdevice = SubDevice::new(subvendor_id, subdevice_id, name.to_owned());
+                subdevice.set_comment(comment);
+                if !self.devices.is_empty() {
+                    self.subdevices.push(subdevice);
+                } else if self.options.collect_orphans {
+                    self.orphan_subdevices.push(subdevice);
+                } else {
+                    return Err(ParseError::OrphanSubdevice { line_number });
+                }
+            }
+            Entry::Class { id, .. } => {
+                let class = if self.options.strict_classes {
+                    Class::try_new(id).map_err(|_| ParseError::UnknownClass {
+                        line_number: Some(line_number),
+                        id,
+                    })?
+                } else {
+                    Class::new(id)
+                };
+                self.finish_class();
+                self.classes.push(class);
+            }
+            Entry::SubClass { id, name } => {
+                self.finish_subclass();
+                let (name, comment) = self.split_comment(name, comment);
+                let mut subclass = SubClass::new(id, name.to_owned());
+                subclass.set_comment(comment);
+                self.subclasses.push(subclass);
+            }
+            Entry::Interface { id, name } => {
+                if self.subclasses.is_empty() {
+                    return Err(ParseError::UnexpectedIndent { line_number });
+                }
+                let (name, comment) = self.split_comment(name, comment);
+                let mut interface = Interface::new(id, name.to_owned());
+                interface.set_comment(comment);
+                self.interfaces.push(interface);
+            }
+        }
+        Ok(())
+    }
+
+    /// Split the trailing comment off the name of an entry, appending it to the comment lines
+    /// above the entry if comments are kept.
+    fn split_comment<'a>(
+        &self,
+        name: &'a str,
+        comment: Option<String>,
+    ) -> (&'a str, Option<String>) {
+        let (name, inline) = split_inline_comment(name);
+        let inline = inline.filter(|_| self.options.keep_comments);
+        let comment = match (comment, inline) {
+            (Some(comment), Some(inline)) => Some(comment + "\n" + inline),
+            (comment, inline) => comment.or_else(|| inline.map(str::to_owned)),
+        };
+        (name, comment)
+    }
+
+    /// Attach the collected subdevices to the last device.
+    fn finish_device(&mut self) {
+        let subdevices = core::mem::take(&mut self.subdevices);
+        if let Some(d) = self.devices.last_mut() {
+            d.set_subdevices(subdevices);
+        }
+    }
+
+    /// Attach the collected devices to the last vendor.
+    fn finish_vendor(&mut self) {
+        self.finish_device();
+        let devices = core::mem::take(&mut self.devices);
+        if let Some(v) = self.vendors.last_mut() {
+            v.set_devices(devices);
+        }
+    }
+
+    /// Remove the last vendor along with the devices collected for it.
+    fn take_vendor(&mut self) -> Option<Vendor> {
+        self.finish_vendor();
+        self.vendors.pop()
+    }
+
+    /// Attach the collected interfaces to the last subclass.
+    fn finish_subclass(&mut self) {
+        let interfaces = core::mem::take(&mut self.interfaces);
+        if let Some(s) = self.subclasses.last_mut() {
+            s.set_interfaces(interfaces);
+        }
+    }
+
+    /// Attach the collected subclasses to the last class.
+    fn finish_class(&mut self) {
+        self.finish_subclass();
+        let subclasses = core::mem::take(&mut self.subclasses);
+        if let Some(c) = self.classes.last_mut() {
+            c.set_subclasses(subclasses);
+        }
+    }
+
+    /// Add in the last ones.
+    fn finish(&mut self) {
+        self.finish_vendor();
+        self.finish_class();
+    }
+}
+
+/// Path of the pci.ids file [PciIds::from_default_path] would parse, found the same way but
+/// without parsing it, e.g. for telling the user which file is used.
+///
+/// Returns `None` if neither the [PCI_IDS_PATH_VAR] environment variable nor any of the
+/// [KNOWN_PATHS] point to a file.
+#[cfg(feature = "std")]
+pub fn find_pci_ids_path() -> Option<PathBuf> {
+    first_file(default_paths())
+}
+
+/// The path in the [PCI_IDS_PATH_VAR] environment variable, unless it's unset or empty, followed
+/// by the [KNOWN_PATHS].
+#[cfg(feature = "std")]
+fn default_paths() -> impl Iterator<Item = PathBuf> {
+    paths_with(std::env::var_os(PCI_IDS_PATH_VAR))
+}
+
+/// The given value of the [PCI_IDS_PATH_VAR] environment variable, unless it's unset or empty,
+/// followed by the [KNOWN_PATHS].
+#[cfg(feature = "std")]
+fn paths_with(from_env: Option<std::ffi::OsString>) -> impl Iterator<Item = PathBuf> {
+    let from_env = from_env.filter(|path| !path.is_empty());
+    let known = KNOWN_PATHS.iter().map(PathBuf::from);
+    from_env.map(PathBuf::from).into_iter().chain(known)
+}
+
+/// The first of the given paths that is a file.
+#[cfg(feature = "std")]
+fn first_file(paths: impl IntoIterator<Item = PathBuf>) -> Option<PathBuf> {
+    paths.into_iter().find(|path| path.is_file())
+}
+
+/// Parse a user supplied hexadecimal id, tolerating whitespace, a `0x` prefix and uppercase digits.
+pub(crate) fn parse_hex_u16(id: &str) -> Result<u16, ParseError> {
+    let trimmed = id.trim();
+    let digits = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+    u16::from_str_radix(digits, 16).map_err(|source| ParseError::InvalidId {
+        id: id.to_owned(),
+        source,
+    })
+}
+
+/// Whether `haystack` contains `needle` when ignoring ASCII case, without allocating.
+pub(crate) fn contains_ignore_ascii_case(haystack: &str, needle: &str) -> bool {
+    let needle = needle.as_bytes();
+    needle.is_empty()
+        || haystack
+            .as_bytes()
+            .windows(needle.len())
+            .any(|window| window.eq_ignore_ascii_case(needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class::SubClass;
+    use crate::device_class::DeviceClass;
+    use crate::ids::{SubDeviceId, VendorId};
+    use crate::pci_ids::{Diagnostic, ParseError, ParseOptions, PciIds, PATH_TO_PCI_IDS};
+    #[cfg(feature = "std")]
+    use crate::pci_ids::{ResolvedClass, ResolvedNames};
+    #[cfg(feature = "std")]
+    use crate::vendor::SubDevice;
+    #[cfg(feature = "std")]
+    use std::path::Path;
+    /// Test the vendors part of the parsed result by picking an example and checking if it is ok
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_vendors_list() {
+        let mut pci_ids = PciIds::new();
+        pci_ids
+            .parse_vendors(Path::new(PATH_TO_PCI_IDS))
+            .unwrap();
+        let res = pci_ids.vendors().iter().find(|&v| {
+            v.id() == 0x0e11
+                && v.name() == "Compaq Computer Corporation"
+                && v.devices()
+                    .iter()
+                    .find(|&d| {
+                        d.id() == 0x0046
+                            && d.name() == "Smart Array 64xx"
+                            && d.subdevices()
+                                .iter()
+                                .find(|&s| {
+                                    s.subvendor_id() == 0x0e11
+                                        && s.subdevice_id() == 0x409d
+                                        && s.name() == "Smart Array 6400 EM"
+                                })
+                                .is_some()
+                    })
+                    .is_some()
+        });
+        assert!(res.is_some());
+    }
+
+    /// Test the classes part of the parsed result by picking an example and checking if it is ok
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_classes_list() {
+        let mut pci_ids = PciIds::new();
+        pci_ids
+            .parse_classes(Path::new(PATH_TO_PCI_IDS))
+            .unwrap();
+        let res = pci_ids.classes().iter().find(|&c| {
+            c.id() == 0x0c
+                && c.class().to_string() == "Serial Bus Controller"
+                && c.subclasses()
+                    .iter()
+                    .find(|&s| {
+                        s.id() == 0x03
+                            && s.name() == "USB controller"
+                            && s.interfaces()
+                                .iter()
+                                .find(|&i| i.id() == 0xfe && i.name() == "USB Device")
+                                .is_some()
+                    })
+                    .is_some()
+        });
+        println!("{:?}", res);
+        assert!(res.is_some());
+    }
+
+    /// Test that the source text is kept when asked for and only then
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_retaining_source() {
+        let data = "0e11  Compaq Computer Corporation\n\t0046  Smart Array 64xx\n";
+        let pci_ids = PciIds::parse_retaining_source(data.to_owned()).unwrap();
+        assert_eq!(pci_ids.raw_source(), Some(data));
+        assert_eq!(pci_ids.vendors()[0].devices()[0].name(), "Smart Array 64xx");
+
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        assert_eq!(pci_ids.raw_source(), None);
+    }
+
+    /// Test that the fragments in a directory are parsed in order and merged together
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_dir() {
+        let dir = std::env::temp_dir().join(format!("pci_id_parse_dir_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("subdir")).unwrap();
+        std::fs::write(
+            dir.join("10-base.ids"),
+            "0e11  Compaq Computer Corporation\n\t0046  Smart Array 64xx\n\t0049  NC7132\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("20-overlay.ids"),
+            "0e11  Compaq\n\t0046  Smart Array\n\t\t0e11 409a  Smart Array 641\n1234  New Vendor\n",
+        )
+        .unwrap();
+
+        let pci_ids = PciIds::parse_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(pci_ids.vendors().len(), 2);
+        let compaq = &pci_ids.vendors()[0];
+        assert_eq!(compaq.name(), "Compaq");
+        assert_eq!(compaq.devices().len(), 2);
+        assert_eq!(compaq.devices()[0].name(), "Smart Array");
+        assert_eq!(
+            compaq.devices()[0].subdevices()[0].name(),
+            "Smart Array 641"
+        );
+        assert_eq!(compaq.devices()[1].name(), "NC7132");
+        assert_eq!(pci_ids.vendors()[1].name(), "New Vendor");
+    }
+
+    /// Test that lines nested deeper than subdevices are rejected instead of misattributed
+    #[test]
+    fn test_too_deeply_nested() {
+        let data = "0e11  Compaq Computer Corporation\n\
+                    \t0046  Smart Array 64xx\n\
+                    \t\t0e11 409a  Smart Array 641\n\
+                    \t\t\t0001  Fourth level\n";
+        let err = PciIds::parse_from_str(data).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::TooDeeplyNested {
+                line_number: 4,
+                depth: 3
+            }
+        ));
+    }
+
+    /// Test that keywords are matched case-insensitively against device names
+    #[test]
+    fn test_vendors_matching_keywords() {
+        let data = "1002  Advanced Micro Devices, Inc. [AMD/ATI]\n\
+                    \t731f  Navi 10 [Radeon RX 5600 OEM/5600 XT / 5700/5700 XT]\n\
+                    \t1478  Navi 10 XL Upstream Port of PCI Express Switch\n\
+                    10de  NVIDIA Corporation\n\
+                    \t1b80  GP104 [GeForce GTX 1080]\n\
+                    8086  Intel Corporation\n\
+                    \t1502  82579LM Gigabit Network Connection (Lewisville)\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        let ids: Vec<u16> = pci_ids
+            .vendors_matching_keywords(&["radeon", "GEFORCE"])
+            .iter()
+            .map(|v| v.id())
+            .collect();
+        assert_eq!(ids, [0x1002, 0x10de]);
+        assert!(pci_ids.vendors_matching_keywords(&[]).is_empty());
+    }
+
+    /// Test that device searches come back in a deterministic order
+    #[test]
+    fn test_search_devices_order() {
+        let data = "10de  NVIDIA Corporation\n\
+                    \t1b81  GP104 [GeForce GTX 1070]\n\
+                    \t1b80  GP104 [GeForce GTX 1080]\n\
+                    1002  Advanced Micro Devices, Inc. [AMD/ATI]\n\
+                    \t731f  Navi 10 [Radeon RX 5600 OEM/5600 XT / 5700/5700 XT]\n\
+                    \t67df  Ellesmere [Radeon RX 470/480/570/570X/580/580X/590]\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+
+        let ids: Vec<(u16, u16)> = pci_ids
+            .search_devices("rx")
+            .iter()
+            .map(|(v, d)| (v.id(), d.id()))
+            .collect();
+        assert_eq!(ids, [(0x1002, 0x67df), (0x1002, 0x731f)]);
+
+        let names: Vec<&str> = pci_ids
+            .search_devices_sorted_by("gp104", |(_, d)| d.name().len())
+            .iter()
+            .map(|(_, d)| d.name())
+            .collect();
+        assert_eq!(
+            names,
+            ["GP104 [GeForce GTX 1070]", "GP104 [GeForce GTX 1080]"]
+        );
+    }
+
+    /// Test resolving ids given as strings, telling malformed input apart from missing entries
+    #[test]
+    fn test_resolve_str() {
+        let data = "10de  NVIDIA Corporation\n\t1b80  GP104 [GeForce GTX 1080]\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+
+        let (vendor, device) = pci_ids.resolve_str("10de", "1b80").unwrap().unwrap();
+        assert_eq!(vendor.name(), "NVIDIA Corporation");
+        assert_eq!(device.name(), "GP104 [GeForce GTX 1080]");
+        assert!(pci_ids
+            .resolve_str(" 0x10DE", "0X1B80\n")
+            .unwrap()
+            .is_some());
+        assert!(pci_ids.resolve_str("10de", "1b81").unwrap().is_none());
+        assert!(matches!(
+            pci_ids.resolve_str("10de", "1g80"),
+            Err(ParseError::InvalidId { id, .. }) if id == "1g80"
+        ));
+        assert!(pci_ids.resolve_str("0x", "1b80").is_err());
+    }
+
+    /// Test that the memory estimate accounts for the lists and names that were parsed
+    #[test]
+    fn test_approximate_memory_usage() {
+        let empty = PciIds::new();
+        assert_eq!(
+            empty.approximate_memory_usage(),
+            std::mem::size_of::<PciIds>()
+        );
+
+        let data = "0e11  Compaq Computer Corporation\n\
+                    \t0046  Smart Array 64xx\n\
+                    \t\t0e11 409a  Smart Array 641\n\
+                    C 0c  Serial bus controller\n\
+                    \t03  USB controller\n\
+                    \t\t30  XHCI\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        let names = "Compaq Computer Corporation".len()
+            + "Smart Array 64xx".len()
+            + "Smart Array 641".len()
+            + "USB controller".len()
+            + "XHCI".len();
+        let lists = std::mem::size_of::<crate::vendor::Vendor>()
+            + std::mem::size_of::<crate::vendor::Device>()
+            + std::mem::size_of::<crate::vendor::SubDevice>()
+            + std::mem::size_of::<crate::class::Class>()
+            + std::mem::size_of::<crate::class::SubClass>()
+            + std::mem::size_of::<crate::class::Interface>();
+        assert!(
+            pci_ids.approximate_memory_usage() >= std::mem::size_of::<PciIds>() + names + lists
+        );
+
+        // Keeping the source adds its bytes on top of the same entries
+        let retained = PciIds::parse_retaining_source(data.to_string()).unwrap();
+        assert_eq!(
+            retained.approximate_memory_usage(),
+            pci_ids.approximate_memory_usage() + data.len()
+        );
+    }
+
+    /// Test resolving lspci style `vendor:device` selectors
+    #[test]
+    fn test_resolve_colon() {
+        let data = "10de  NVIDIA Corporation\n\t1b80  GP104 [GeForce GTX 1080]\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+
+        let (vendor, device) = pci_ids.resolve_colon("10de:1b80").unwrap().unwrap();
+        assert_eq!(vendor.id(), 0x10de);
+        assert_eq!(device.unwrap().name(), "GP104 [GeForce GTX 1080]");
+        for selector in ["10de", "10de:", "0x10DE"] {
+            let (vendor, device) = pci_ids.resolve_colon(selector).unwrap().unwrap();
+            assert_eq!(vendor.id(), 0x10de);
+            assert!(device.is_none());
+        }
+        assert!(pci_ids.resolve_colon("10de:1b81").unwrap().is_none());
+        assert!(pci_ids.resolve_colon("1234:1b80").unwrap().is_none());
+
+        for selector in ["", ":1b80", "10de:1b80:00"] {
+            assert!(matches!(
+                pci_ids.resolve_colon(selector),
+                Err(ParseError::MalformedSelector { .. })
+            ));
+        }
+        assert!(matches!(
+            pci_ids.resolve_colon("10de:xyz"),
+            Err(ParseError::InvalidId { .. })
+        ));
+    }
+
+    /// Test that the id only parse agrees with the full parse on the real file
+    #[test]
+    fn test_parse_ids_only() {
+        let data = std::fs::read_to_string(PATH_TO_PCI_IDS).unwrap();
+        let topology = PciIds::parse_ids_only(&data).unwrap();
+        assert!(topology.contains_vendor(0x0e11));
+        assert!(topology.contains_device(0x0e11, 0x0046));
+        assert!(topology.contains_subdevice(0x0e11, 0x0046, 0x0e11, 0x409d));
+        assert!(!topology.contains_subdevice(0x0e11, 0x0046, 0x409d, 0x0e11));
+        assert!(topology.contains_interface(0x0c, 0x03, 0xfe));
+        assert!(!topology.contains_subclass(0x0c, 0x7f));
+
+        let pci_ids = PciIds::parse_from_str(&data).unwrap();
+        for vendor in pci_ids.vendors() {
+            assert!(topology.contains_vendor(vendor.id()));
+            for device in vendor.devices() {
+                assert!(topology.contains_device(vendor.id(), device.id()));
+            }
+        }
+        assert_eq!(topology.classes().len(), pci_ids.classes().len());
+    }
+
+    /// Test that codenames are collected only for devices that have one
+    #[test]
+    fn test_codenames() {
+        let data = "10de  NVIDIA Corporation\n\
+                    \t1b80  GP104 [GeForce GTX 1080]\n\
+                    \t1ad8  TU104 USB 3.1 Host Controller\n\
+                    1002  Advanced Micro Devices, Inc. [AMD/ATI]\n\
+                    \t731f  Navi 10 [Radeon RX 5600 OEM/5600 XT / 5700/5700 XT]\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        let codenames: Vec<(u16, u16, &str)> = pci_ids
+            .codenames()
+            .into_iter()
+            .map(|(v, d, codename)| (v.id(), d.id(), codename))
+            .collect();
+        assert_eq!(
+            codenames,
+            [(0x10de, 0x1b80, "GP104"), (0x1002, 0x731f, "Navi 10")]
+        );
+    }
+
+    /// Test that only vendors with subdevices somewhere below them are returned
+    #[test]
+    fn test_vendors_with_subdevices() {
+        let data = "0e11  Compaq Computer Corporation\n\
+                    \t0001  PCI to EISA Bridge\n\
+                    \t0046  Smart Array 64xx\n\
+                    \t\t0e11 409a  Smart Array 641\n\
+                    1234  Other Vendor\n\
+                    \t0001  Device without subdevices\n\
+                    5678  Empty Vendor\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        let ids: Vec<u16> = pci_ids.vendors_with_subdevices().map(|v| v.id()).collect();
+        assert_eq!(ids, [0x0e11]);
+    }
+
+    /// Test that a file ending on a subdevice line without a trailing newline keeps that subdevice
+    #[test]
+    fn test_no_trailing_newline() {
+        let data = "0e11  Compaq Computer Corporation\n\
+                    \t0046  Smart Array 64xx\n\
+                    \t\t0e11 409a  Smart Array 641\n\
+                    \t\t0e11 409d  Smart Array 6400 EM";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        let subdevices = pci_ids.vendors()[0].devices()[0].subdevices();
+        assert_eq!(subdevices.len(), 2);
+        assert_eq!(subdevices[1].subdevice_id(), 0x409d);
+        assert_eq!(subdevices[1].name(), "Smart Array 6400 EM");
+
+        let data = "C 0c  Serial bus controller\n\t03  USB controller\n\t\tfe  USB Device";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        let interfaces = pci_ids.classes()[0].subclasses()[0].interfaces();
+        assert_eq!(interfaces.len(), 1);
+        assert_eq!(interfaces[0].name(), "USB Device");
+    }
+
+    /// Test that the children of the last device of a vendor and the last subclass of a class are
+    /// attached to them rather than lost when the next vendor or class starts
+    #[test]
+    fn test_last_children_attached() {
+        let data = "0e11  Compaq Computer Corporation\n\
+                    \t0046  Smart Array 64xx\n\
+                    \t\t0e11 409a  Smart Array 641\n\
+                    1234  Other Vendor\n\
+                    \t0001  Some device\n\
+                    C 01  Mass storage controller\n\
+                    \t06  SATA controller\n\
+                    \t\t01  AHCI 1.0\n\
+                    C 02  Network controller\n\
+                    \t00  Ethernet controller\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        let devices = pci_ids.vendors()[0].devices();
+        assert_eq!(devices[0].subdevices().len(), 1);
+        assert!(pci_ids.vendors()[1].devices()[0].subdevices().is_empty());
+        let subclasses = pci_ids.classes()[0].subclasses();
+        assert_eq!(subclasses[0].interfaces().len(), 1);
+        assert!(pci_ids.classes()[1].subclasses()[0].interfaces().is_empty());
+    }
+
+    /// Test that comments directly above an entry are kept on request and driver hints read from
+    /// them
+    #[test]
+    fn test_comments_and_driver_hint() {
+        let data = "# Compaq vendor comment\n\
+                    0e11  Compaq Computer Corporation\n\
+                    # Needs a firmware blob\n\
+                    # Driver: cciss\n\
+                    \t0046  Smart Array 64xx\n\
+                    \t0047  No comment\n\
+                    # driver: detached\n\
+                    \n\
+                    \t0048  Blank line in between\n";
+        let options = ParseOptions {
+            keep_comments: true,
+            ..ParseOptions::default()
+        };
+        let pci_ids = PciIds::parse_with_options(data, options).unwrap();
+        let compaq = &pci_ids.vendors()[0];
+        assert_eq!(compaq.comment(), Some("Compaq vendor comment"));
+        assert_eq!(
+            compaq.devices()[0].comment(),
+            Some("Needs a firmware blob\nDriver: cciss")
+        );
+        assert_eq!(pci_ids.driver_hint(0x0e11, 0x0046), Some("cciss"));
+        assert_eq!(pci_ids.driver_hint(0x0e11, 0x0047), None);
+        assert_eq!(pci_ids.driver_hint(0x0e11, 0x0048), None);
+
+        let pci_ids = PciIds::parse_with_options(data, ParseOptions::default()).unwrap();
+        assert_eq!(pci_ids.vendors()[0].comment(), None);
+        assert_eq!(pci_ids.driver_hint(0x0e11, 0x0046), None);
+    }
+
+    /// Test finding every place an interface name is used in the real file
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_interfaces_named() {
+        let mut pci_ids = PciIds::new();
+        pci_ids.parse_classes(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let codes: Vec<(u8, u8, u8)> = pci_ids
+            .interfaces_named("serial storage BUS")
+            .iter()
+            .map(|(c, s, i)| (c.id(), s.id(), i.id()))
+            .collect();
+        assert_eq!(codes, [(0x01, 0x06, 0x02), (0x01, 0x07, 0x01)]);
+        assert!(pci_ids.interfaces_named("Serial Storage").is_empty());
+    }
+
+    /// Test that strict class parsing accepts the real file but rejects unknown class ids
+    #[test]
+    fn test_strict_classes() {
+        let options = ParseOptions {
+            strict_classes: true,
+            ..ParseOptions::default()
+        };
+        let data = std::fs::read_to_string(PATH_TO_PCI_IDS).unwrap();
+        PciIds::parse_with_options(&data, options).unwrap();
+
+        let data = "C 0c  Serial bus controller\n\t03  USB controller\nC 55  Made up class\n";
+        let err = PciIds::parse_with_options(data, options).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::UnknownClass {
+                line_number: Some(3),
+                id: 0x55
+            }
+        ));
+
+        let pci_ids = PciIds::parse_with_options(data, ParseOptions::default()).unwrap();
+        assert_eq!(pci_ids.classes()[1].class(), DeviceClass::Other(0x55));
+    }
+
+    /// Test that subdevices without a device are rejected, or collected when asked to
+    #[test]
+    fn test_orphan_subdevices() {
+        let data = "0e11  Compaq Computer Corporation\n\
+                    \t0046  Smart Array 64xx\n\
+                    1234  Other Vendor\n\
+                    \t\t0e11 409a  Smart Array 641\n\
+                    \t0001  Some device\n\
+                    \t\t1234 0001  Some subdevice\n";
+        let err = PciIds::parse_with_options(data, ParseOptions::default()).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::OrphanSubdevice { line_number: 4 }
+        ));
+
+        let options = ParseOptions {
+            collect_orphans: true,
+            ..ParseOptions::default()
+        };
+        let pci_ids = PciIds::parse_with_options(data, options).unwrap();
+        assert_eq!(pci_ids.orphan_subdevices().len(), 1);
+        assert_eq!(pci_ids.orphan_subdevices()[0].name(), "Smart Array 641");
+        assert!(pci_ids.vendors()[0].devices()[0].subdevices().is_empty());
+        assert_eq!(pci_ids.vendors()[1].devices()[0].subdevices().len(), 1);
+    }
+
+    /// Test that the class tree carries the names of every level
+    #[test]
+    fn test_class_tree() {
+        let data = "C 01  Mass storage controller\n\
+                    \t01  IDE interface\n\
+                    \t\t00  ISA Compatibility mode-only controller\n\
+                    \t\t05  PCI native mode-only controller\n\
+                    \t02  Floppy disk controller\n\
+                    C 02  Network controller\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        let tree = pci_ids.class_tree();
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].id, 0x01);
+        assert_eq!(tree[0].name, "Mass Storage Controller");
+        assert_eq!(tree[0].subclasses.len(), 2);
+        assert_eq!(tree[0].subclasses[0].name, "IDE interface");
+        assert_eq!(tree[0].subclasses[0].interfaces.len(), 2);
+        assert_eq!(tree[0].subclasses[0].interfaces[1].id, 0x05);
+        assert_eq!(
+            tree[0].subclasses[0].interfaces[1].name,
+            "PCI native mode-only controller"
+        );
+        assert!(tree[0].subclasses[1].interfaces.is_empty());
+        assert!(tree[1].subclasses.is_empty());
+    }
+
+    /// Test that a tab between id and name works just like two spaces
+    #[test]
+    fn test_tab_separator() {
+        let data = "0e11\tCompaq Computer Corporation\n\
+                    \t0046 \t Smart Array 64xx\n\
+                    \t\t0e11 409a\tSmart Array 641\n\
+                    C 01\tMass storage controller\n\
+                    \t01\t\tIDE interface\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        let vendor = &pci_ids.vendors()[0];
+        assert_eq!(vendor.id(), 0x0e11);
+        assert_eq!(vendor.name(), "Compaq Computer Corporation");
+        let device = &vendor.devices()[0];
+        assert_eq!(device.id(), 0x0046);
+        assert_eq!(device.name(), "Smart Array 64xx");
+        let subdevice = &device.subdevices()[0];
+        assert_eq!(subdevice.subvendor_id(), 0x0e11);
+        assert_eq!(subdevice.subdevice_id(), 0x409a);
+        assert_eq!(subdevice.name(), "Smart Array 641");
+        let subclass = &pci_ids.classes()[0].subclasses()[0];
+        assert_eq!(subclass.id(), 0x01);
+        assert_eq!(subclass.name(), "IDE interface");
+    }
+
+    /// Test that a range of vendor ids gives the matching slice of vendors
+    #[test]
+    fn test_vendors_in_range() {
+        let data = "0e11  Compaq Computer Corporation\n\
+                    10de  NVIDIA Corporation\n\
+                    1234  Technical Corp.\n\
+                    8086  Intel Corporation\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        let ids = |range| {
+            pci_ids
+                .vendors_in_range(range)
+                .iter()
+                .map(|v| v.id())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(0x1000..0x8086), [0x10de, 0x1234]);
+        assert_eq!(ids(0x0000..0xffff), [0x0e11, 0x10de, 0x1234, 0x8086]);
+        assert_eq!(ids(0x0e11..0x0e12), [0x0e11]);
+        assert!(ids(0x2000..0x3000).is_empty());
+    }
+
+    /// Test that progress is reported up to the total number of lines
+    #[test]
+    fn test_parse_with_progress() {
+        let data = std::fs::read_to_string(PATH_TO_PCI_IDS).unwrap();
+        let total = data.lines().count();
+        let mut reports = Vec::new();
+        let pci_ids =
+            PciIds::parse_with_progress(&data, |done, all| reports.push((done, all))).unwrap();
+        assert!(!pci_ids.vendors().is_empty());
+        assert!(reports.len() > 2);
+        assert_eq!(reports.first(), Some(&(0, total)));
+        assert_eq!(reports.last(), Some(&(total, total)));
+        assert!(reports.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    /// Test that a subset only holds the installed hardware and every class
+    #[cfg(feature = "sysfs")]
+    #[test]
+    fn test_subset_for_devices() {
+        use crate::sysfs::SystemDevice;
+        use crate::vendor::SubsystemId;
+
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let system = [
+            SystemDevice {
+                address: "0000:03:00.0".to_owned(),
+                vendor_id: 0x0e11,
+                device_id: 0x0046,
+                subsystem: Some(SubsystemId::new(0x0e11, 0x409a)),
+                class_code: Some(0x010400),
+            },
+            SystemDevice {
+                address: "0000:01:00.0".to_owned(),
+                vendor_id: 0x10de,
+                device_id: 0x1b80,
+                subsystem: None,
+                class_code: None,
+            },
+        ];
+        let subset = pci_ids.subset_for_devices(&system);
+        assert_eq!(subset.classes(), pci_ids.classes());
+        assert_eq!(subset.vendors().len(), 2);
+
+        let compaq = &subset.vendors()[0];
+        assert_eq!(compaq.name(), "Compaq Computer Corporation");
+        assert_eq!(compaq.devices().len(), 1);
+        assert_eq!(compaq.devices()[0].name(), "Smart Array 64xx");
+        assert_eq!(compaq.devices()[0].subdevices().len(), 1);
+        assert_eq!(compaq.devices()[0].subdevices()[0].subdevice_id(), 0x409a);
+
+        let nvidia = &subset.vendors()[1];
+        assert_eq!(nvidia.devices().len(), 1);
+        assert!(nvidia.devices()[0].subdevices().is_empty());
+
+        assert!(subset.approximate_memory_usage() < pci_ids.approximate_memory_usage() / 10);
+    }
+
+    /// Test that the running system can be read if it exposes sysfs
+    #[cfg(feature = "sysfs")]
+    #[test]
+    fn test_subset_for_system() {
+        if !Path::new("/sys/bus/pci/devices").exists() {
+            return;
+        }
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let subset = pci_ids.subset_for_system().unwrap();
+        assert_eq!(subset.classes(), pci_ids.classes());
+        assert!(subset.vendors().len() <= pci_ids.vendors().len());
+    }
+
+    /// Test membership checks for present and absent vendors, devices and classes
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_contains() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        assert!(pci_ids.contains_vendor(0x0e11));
+        assert!(pci_ids.contains_vendor(0xffff));
+        assert!(!pci_ids.contains_vendor(0x0abc));
+
+        let compaq = pci_ids.vendor_by_id(0x0e11).unwrap();
+        assert!(compaq.contains_device(0x0046));
+        assert!(!compaq.contains_device(0x0047));
+
+        assert!(pci_ids.contains_class(0x0c));
+        assert!(pci_ids.contains_class(0xff));
+        assert!(!pci_ids.contains_class(0x14));
+    }
+
+    /// Test that vendors are found by id
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_vendor_by_id() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let compaq = pci_ids.vendor_by_id(0x0e11).unwrap();
+        assert_eq!(compaq.id(), 0x0e11);
+        assert_eq!(compaq.name(), "Compaq Computer Corporation");
+        assert!(pci_ids.vendor_by_id(0x0abc).is_none());
+        // Not absent as one might expect, the file lists it to name the value read from empty slots
+        assert_eq!(
+            pci_ids.vendor_by_id(0xffff).map(|v| v.name()),
+            Some("Illegal Vendor ID")
+        );
+    }
+
+    /// Test resolving names with all four ids, only vendor and device, and unknown ids
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_resolve() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let names = pci_ids.resolve(
+            0x0e11,
+            0x0046,
+            Some(VendorId(0x0e11)),
+            Some(SubDeviceId(0x409a)),
+        );
+        assert_eq!(
+            names,
+            ResolvedNames {
+                vendor: Some("Compaq Computer Corporation"),
+                device: Some("Smart Array 64xx"),
+                subvendor: Some("Compaq Computer Corporation"),
+                subdevice: Some("Smart Array 641"),
+            }
+        );
+
+        let names = pci_ids.resolve(0x0e11, 0x0046, None, None);
+        assert_eq!(names.vendor, Some("Compaq Computer Corporation"));
+        assert_eq!(names.device, Some("Smart Array 64xx"));
+        assert_eq!(names.subvendor, None);
+        assert_eq!(names.subdevice, None);
+
+        let names = pci_ids.resolve(
+            0x0e11,
+            0xfffe,
+            Some(VendorId(0x0e11)),
+            Some(SubDeviceId(0x409a)),
+        );
+        assert_eq!(names.vendor, Some("Compaq Computer Corporation"));
+        assert_eq!(names.device, None);
+        assert_eq!(names.subvendor, Some("Compaq Computer Corporation"));
+        assert_eq!(names.subdevice, None);
+
+        assert_eq!(
+            pci_ids.resolve(0x0abc, 0x0046, None, None),
+            ResolvedNames::default()
+        );
+    }
+
+    /// Test that a bad hex id is reported along with its line
+    #[test]
+    fn test_invalid_hex_line_number() {
+        let data = "0e11  Compaq Computer Corporation\n\
+                    \t0046  Smart Array 64xx\n\
+                    \t00zz  Not an id\n";
+        let err = PciIds::parse_with_options(data, ParseOptions::default()).unwrap_err();
+        assert!(matches!(err, ParseError::InvalidHex { line_number: 3, .. }));
+    }
+
+    /// Test that malformed lines are errors instead of panics
+    #[test]
+    fn test_malformed_lines() {
+        let parse = |data: &str| PciIds::parse_with_options(data, ParseOptions::default());
+        assert!(matches!(
+            parse("0e11 Compaq Computer Corporation\n"),
+            Err(ParseError::MissingSeparator { line_number: 1 })
+        ));
+        assert!(matches!(
+            parse("0e11  Compaq Computer Corporation\n\t0046  Smart\n\t\t0e11409a  Smart Array\n"),
+            Err(ParseError::MissingSeparator { line_number: 3 })
+        ));
+        assert!(matches!(
+            parse("0e11  Compaq Computer Corporation\n  0046  Smart Array 64xx\n"),
+            Err(ParseError::UnexpectedIndent { line_number: 2 })
+        ));
+        assert!(matches!(
+            parse("\t0046  Smart Array 64xx\n"),
+            Err(ParseError::UnexpectedIndent { line_number: 1 })
+        ));
+        assert!(matches!(
+            parse(
+                "C 01  Mass storage controller\n\t\t00  ISA Compatibility mode-only controller\n"
+            ),
+            Err(ParseError::UnexpectedIndent { line_number: 2 })
+        ));
+        assert!(matches!(
+            parse("C 01  Mass storage controller\n01  IDE interface\n"),
+            Err(ParseError::UnexpectedIndent { line_number: 2 })
+        ));
+    }
+
+    /// Test parsing contents that are already in memory, in full or one section at a time
+    #[test]
+    fn test_parse_from_str() {
+        let data = "# List of PCI ID's\n\
+                    0e11  Compaq Computer Corporation\n\
+                    \t0046  Smart Array 64xx\n\
+                    \t\t0e11 409a  Smart Array 641\n\
+                    C 01  Mass storage controller\n\
+                    \t01  IDE interface\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        assert_eq!(pci_ids.vendors().len(), 1);
+        assert_eq!(pci_ids.vendors()[0].devices()[0].subdevices().len(), 1);
+        assert_eq!(pci_ids.classes().len(), 1);
+        assert!(pci_ids.raw_source().is_none());
+
+        let mut vendors_only = PciIds::new();
+        vendors_only.parse_vendors_from_str(data).unwrap();
+        assert_eq!(vendors_only.vendors(), pci_ids.vendors());
+        assert!(vendors_only.classes().is_empty());
+
+        let mut classes_only = PciIds::new();
+        classes_only.parse_classes_from_str(data).unwrap();
+        assert!(classes_only.vendors().is_empty());
+        assert_eq!(classes_only.classes(), pci_ids.classes());
+    }
+
+    /// Test that reading line by line gives the same result as parsing the whole string
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_from_reader() {
+        let data = "0e11  Compaq Computer Corporation\r\n\
+                    \t0046  Smart Array 64xx\n\
+                    \t\t0e11 409a  Smart Array 641\n\
+                    \n\
+                    C 01  Mass storage controller\n\
+                    \t01  IDE interface\n";
+        let pci_ids = PciIds::parse_from_reader(std::io::Cursor::new(data.as_bytes())).unwrap();
+        assert_eq!(pci_ids, PciIds::parse_from_str(data).unwrap());
+        assert_eq!(pci_ids.vendors()[0].name(), "Compaq Computer Corporation");
+
+        let err = PciIds::parse_from_reader(&b"0e11  Compaq\n\t00zz  Broken\n"[..]).unwrap_err();
+        assert!(matches!(err, ParseError::InvalidHex { line_number: 2, .. }));
+    }
+
+    /// Test that the path from the environment variable is preferred and that missing files are
+    /// listed in the error
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_from_default_path() {
+        use crate::pci_ids::{paths_with, KNOWN_PATHS};
+        use std::ffi::OsString;
+        use std::path::PathBuf;
+
+        let dir = std::env::temp_dir().join(format!("pci-id-default-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let fixture = dir.join("pci.ids");
+        std::fs::write(&fixture, "0e11  Compaq Computer Corporation\n").unwrap();
+
+        // The environment is shared with the tests running in parallel, so the variable is
+        // passed in rather than set
+        let pci_ids =
+            PciIds::parse_first_existing(paths_with(Some(fixture.clone().into()))).unwrap();
+        assert_eq!(pci_ids.vendor_count(), 1);
+        assert!(pci_ids.vendor_by_id(0x0e11).is_some());
+
+        let missing = dir.join("missing.ids");
+        let paths = [missing.clone(), fixture.clone()];
+        assert_eq!(PciIds::parse_first_existing(paths).unwrap(), pci_ids);
+        match PciIds::parse_first_existing([missing.clone(), dir.clone()]) {
+            Err(ParseError::NotFound { tried }) => assert_eq!(tried, [missing, dir.clone()]),
+            result => panic!("expected NotFound, got {result:?}"),
+        }
+        let known: Vec<PathBuf> = KNOWN_PATHS.iter().map(PathBuf::from).collect();
+        assert_eq!(paths_with(None).collect::<Vec<_>>(), known);
+        assert_eq!(paths_with(Some(OsString::new())).collect::<Vec<_>>(), known);
+        assert_eq!(
+            PciIds::parse_first_existing(known).unwrap(),
+            PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap()
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Test that the first existing file is found, and nothing among missing or no candidates
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_find_pci_ids_path() {
+        use crate::pci_ids::{find_pci_ids_path, first_file, KNOWN_PATHS};
+        use std::path::PathBuf;
+
+        assert!(find_pci_ids_path().is_some_and(|path| path.is_file()));
+        let known = KNOWN_PATHS.iter().map(PathBuf::from);
+        assert_eq!(first_file(known), Some(PathBuf::from(PATH_TO_PCI_IDS)));
+
+        assert_eq!(first_file([]), None);
+        let missing = std::env::temp_dir().join(format!("pci-id-missing-{}", std::process::id()));
+        assert_eq!(first_file([missing, std::env::temp_dir()]), None);
+    }
+
+    /// Test that parse errors describe themselves and chain to their cause
+    #[test]
+    fn test_parse_error_display_and_source() {
+        use core::error::Error;
+        use core::num::ParseIntError;
+
+        fn parse(data: &str) -> Result<PciIds, Box<dyn Error>> {
+            Ok(PciIds::parse_from_str(data)?)
+        }
+
+        let err = parse("10de  NVIDIA Corporation\n\t1bzz  GP104\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: invalid hexadecimal id");
+        assert!(err
+            .source()
+            .is_some_and(|source| source.is::<ParseIntError>()));
+
+        let err = parse("10de  NVIDIA Corporation\n\t\t\t1b80  GP104\n").unwrap_err();
+        assert!(err.to_string().starts_with("line 2: "));
+        assert!(err.source().is_none());
+
+        let err = ParseError::UnknownClass {
+            line_number: None,
+            id: 0x7f,
+        };
+        assert_eq!(err.to_string(), "unknown class 7f");
+    }
+
+    /// Test that invalid UTF-8 is replaced by default, and reported with its offset otherwise
+    #[test]
+    fn test_parse_from_bytes() {
+        let data = b"0e11  Compaq Computer Corporation\n\t0046  Smart Array \xff64xx\n";
+        let pci_ids = PciIds::parse_from_bytes(data).unwrap();
+        let compaq = pci_ids.vendor_by_id(0x0e11).unwrap();
+        assert_eq!(compaq.name(), "Compaq Computer Corporation");
+        assert_eq!(compaq.devices()[0].name(), "Smart Array \u{fffd}64xx");
+
+        // Invalid bytes in an id, or cut off at the very end, don't panic either
+        assert!(matches!(
+            PciIds::parse_from_bytes(b"0e11  Compaq\n\t00\xc3  Smart Array\n"),
+            Err(ParseError::InvalidHex { line_number: 2, .. })
+        ));
+        let pci_ids = PciIds::parse_from_bytes(b"0e11  Compaq \xe2\x82").unwrap();
+        assert_eq!(pci_ids.vendors()[0].name(), "Compaq \u{fffd}");
+
+        let options = ParseOptions {
+            lossy_utf8: false,
+            ..ParseOptions::default()
+        };
+        let err = PciIds::parse_bytes_with_options(data, options).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::InvalidUtf8 {
+                line_number: 2,
+                byte_offset: 53,
+                ..
+            }
+        ));
+        assert_eq!(err.to_string(), "line 2: invalid UTF-8 at byte 53");
+        let valid = PciIds::parse_bytes_with_options(b"0e11  Compaq\n", options).unwrap();
+        assert_eq!(valid.vendors()[0].name(), "Compaq");
+    }
+
+    /// Test that files with invalid UTF-8 are read like [PciIds::parse_from_bytes] reads them
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_files_lossy() {
+        let data = b"0e11  Compaq Computer Corporation\r\n\t0046  Smart Array \xff64xx\n";
+        let expected = PciIds::parse_from_bytes(data).unwrap();
+        let path = std::env::temp_dir().join(format!("pci-id-lossy-{}", std::process::id()));
+        std::fs::write(&path, data).unwrap();
+
+        assert_eq!(PciIds::parse_pci_id_list(&path).unwrap(), expected);
+        let read = PciIds::parse_from_reader(std::io::BufReader::new(&data[..])).unwrap();
+        assert_eq!(read, expected);
+        assert_eq!(read.vendors()[0].name(), "Compaq Computer Corporation");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Test that the embedded snapshot parses and knows well-known vendors
+    #[cfg(feature = "bundled")]
+    #[test]
+    fn test_from_bundled() {
+        let pci_ids = PciIds::from_bundled().unwrap();
+        let intel = pci_ids.vendor_by_id(0x8086).unwrap();
+        assert_eq!(intel.name(), "Intel Corporation");
+        assert!(!pci_ids.classes().is_empty());
+    }
+
+    /// Test that the real file survives a round trip through JSON
+    #[cfg(all(feature = "std", feature = "serde"))]
+    #[test]
+    fn test_serde_round_trip() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let json = serde_json::to_string(&pci_ids).unwrap();
+        let deserialized: PciIds = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, pci_ids);
+
+        // Classes are stored as their byte rather than the name of the variant
+        let class = serde_json::to_string(&pci_ids.classes()[0].class()).unwrap();
+        assert_eq!(class, "0");
+    }
+
+    /// Test the structure of the JSON export and that it is valid JSON
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_to_json() {
+        let data = "# Version: 2024.01.01\n\
+                    10de  NVIDIA Corporation\n\
+                    \t1b80  GP104 [GeForce GTX 1080]\n\
+                    \t\t1043 8591  ROG STRIX GTX 1080\n\
+                    \t\t1043 8599  ROG STRIX GTX 1080 OC\n\
+                    0e11  Compaq Computer Corporation\n\
+                    C 0c  Serial bus controller\n\
+                    \t03  USB controller\n\
+                    \t\t30  XHCI\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&pci_ids.to_json().unwrap()).unwrap();
+
+        assert_eq!(json["version"], "2024.01.01");
+        assert!(json["date"].is_null());
+        let nvidia = &json["vendors"]["10de"];
+        assert_eq!(nvidia["name"], "NVIDIA Corporation");
+        let device = &nvidia["devices"]["1b80"];
+        assert_eq!(device["name"], "GP104 [GeForce GTX 1080]");
+        assert_eq!(device["subdevices"]["1043 8599"], "ROG STRIX GTX 1080 OC");
+        assert_eq!(device["subdevices"].as_object().unwrap().len(), 2);
+        assert!(json["vendors"]["0e11"]["devices"]
+            .as_object()
+            .unwrap()
+            .is_empty());
+        let usb = &json["classes"]["0c"]["subclasses"]["03"];
+        assert_eq!(usb["interfaces"]["30"], "XHCI");
+    }
+
+    /// Test resolving full class codes, including unknown lower levels
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_resolve_class() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        assert_eq!(
+            pci_ids.resolve_class(0x0c0330),
+            ResolvedClass {
+                class: Some("Serial Bus Controller"),
+                subclass: Some("USB controller"),
+                interface: Some("XHCI"),
+            }
+        );
+
+        let names = pci_ids.resolve_class(0x0c03ee);
+        assert_eq!(names.class, Some("Serial Bus Controller"));
+        assert_eq!(names.subclass, Some("USB controller"));
+        assert_eq!(names.interface, None);
+
+        let names = pci_ids.resolve_class(0x0c7f00);
+        assert_eq!(names.class, Some("Serial Bus Controller"));
+        assert_eq!(names.subclass, None);
+        assert_eq!(names.interface, None);
+
+        assert_eq!(pci_ids.resolve_class(0x7f0000), ResolvedClass::default());
+    }
+
+    /// Test joining the names of the class levels that were found
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_resolved_class_full_name() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        assert_eq!(
+            pci_ids.resolve_class(0x0c0330).full_name(" / "),
+            "Serial Bus Controller / USB controller / XHCI"
+        );
+        assert_eq!(
+            pci_ids.resolve_class(0x0c03ee).full_name(" / "),
+            "Serial Bus Controller / USB controller"
+        );
+        assert_eq!(
+            pci_ids.resolve_class(0x010601).full_name(": "),
+            "Mass Storage Controller: SATA controller: AHCI 1.0"
+        );
+        assert_eq!(ResolvedClass::default().full_name(" / "), "");
+    }
+
+    /// Test searching vendors by a part of their name
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_find_vendors_by_name() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let nvidia = pci_ids.find_vendors_by_name("nvidia");
+        assert!(nvidia.iter().any(|v| v.id() == 0x10de));
+        assert!(nvidia
+            .iter()
+            .all(|v| v.name().to_ascii_lowercase().contains("nvidia")));
+        assert!(pci_ids.find_vendors_by_name("no such vendor").is_empty());
+        assert_eq!(
+            pci_ids.find_vendors_by_name("").len(),
+            pci_ids.vendors().len()
+        );
+    }
+
+    /// Test that the flattened device iterator covers every vendor's devices
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_all_devices() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let total: usize = pci_ids.iter_vendors().map(|v| v.devices().len()).sum();
+        assert!(total > 10_000);
+        assert_eq!(pci_ids.all_devices().count(), total);
+        assert_eq!(pci_ids.iter_vendors().count(), pci_ids.vendors().len());
+        assert_eq!(pci_ids.iter_classes().count(), pci_ids.classes().len());
+
+        let (vendor, device) = pci_ids
+            .all_devices()
+            .find(|(_, d)| d.name() == "Smart Array 64xx")
+            .unwrap();
+        assert_eq!((vendor.id(), device.id()), (0x0e11, 0x0046));
+    }
+
+    /// Test that version and date are taken from the header only
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_version_and_date() {
+        let data = "#\n\
+                    #\tList of PCI ID's\n\
+                    #\n\
+                    #\tVersion: 2024.03.31\n\
+                    #\tDate:    2024-03-31 03:15:02\n\
+                    #\n\
+                    0e11  Compaq Computer Corporation\n\
+                    # Version: 1999.01.01\n\
+                    \t0046  Smart Array 64xx\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        assert_eq!(pci_ids.version(), Some("2024.03.31"));
+        assert_eq!(pci_ids.date(), Some("2024-03-31 03:15:02"));
+
+        let pci_ids = PciIds::parse_from_str("0e11  Compaq Computer Corporation\n").unwrap();
+        assert_eq!(pci_ids.version(), None);
+        assert_eq!(pci_ids.date(), None);
+
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        assert!(pci_ids.version().is_some());
+        assert!(pci_ids.date().is_some());
+    }
+
+    /// Test that the two ids of a subdevice may be separated by any amount of whitespace
+    #[test]
+    fn test_subdevice_id_whitespace() {
+        let data = "1043  ASUSTeK Computer Inc.\n\
+                    \t8826  Some device\n\
+                    \t\t1043  8826  With two spaces\n\
+                    \t\t1043\t8827\tWith tabs\n\
+                    \t\t1043 \t 8828  Mixed\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        let ids: Vec<(u16, u16, &str)> = pci_ids.vendors()[0].devices()[0]
+            .subdevices()
+            .iter()
+            .map(|s| (s.subvendor_id(), s.subdevice_id(), s.name()))
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                (0x1043, 0x8826, "With two spaces"),
+                (0x1043, 0x8827, "With tabs"),
+                (0x1043, 0x8828, "Mixed"),
+            ]
+        );
+    }
+
+    /// Test that only `C ` starts a class, not any line starting with a `C`
+    #[test]
+    fn test_class_marker() {
+        let data = "C0de  Uppercase vendor\n\
+                    \tCafe  Uppercase device\n\
+                    C 0c  Serial bus controller\n\
+                    \t03  USB controller\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        assert_eq!(pci_ids.vendors().len(), 1);
+        assert_eq!(pci_ids.vendors()[0].id(), 0xc0de);
+        assert_eq!(pci_ids.vendors()[0].devices()[0].id(), 0xcafe);
+        assert_eq!(pci_ids.classes().len(), 1);
+        assert_eq!(
+            pci_ids.classes()[0].subclasses()[0].name(),
+            "USB controller"
+        );
+    }
+
+    /// Test that the reserved entries at the end of both sections keep exactly their own data
+    #[test]
+    fn test_reserved_tail() {
+        let data = std::fs::read_to_string(PATH_TO_PCI_IDS).unwrap();
+        let pci_ids = PciIds::parse_from_str(&data).unwrap();
+
+        let illegal = pci_ids.vendors().last().unwrap();
+        assert_eq!(illegal.id(), 0xffff);
+        assert_eq!(illegal.name(), "Illegal Vendor ID");
+        assert!(illegal.devices().is_empty());
+        let vmware = pci_ids.vendor_by_id(0xfffe).unwrap();
+        assert_eq!(vmware.device_ids(), [0x0710]);
+
+        let unassigned = pci_ids.classes().last().unwrap();
+        assert_eq!(unassigned.id(), 0xff);
+        assert!(unassigned.subclasses().is_empty());
+
+        // Every line has ended up in exactly one entry of its own section
+        let classes_start = data.lines().position(|l| l.starts_with("C ")).unwrap();
+        let (vendor_section, class_section) = data.split_at(
+            data.lines()
+                .take(classes_start)
+                .map(|l| l.len() + 1)
+                .sum::<usize>(),
+        );
+        let count = |section: &str, tabs: usize| {
+            section
+                .lines()
+                .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                .filter(|l| l.bytes().take_while(|&b| b == b'\t').count() == tabs)
+                .count()
+        };
+        let subdevices: usize = pci_ids
+            .all_devices()
+            .map(|(_, d)| d.subdevices().len())
+            .sum();
+        let subclasses: Vec<&SubClass> = pci_ids
+            .iter_classes()
+            .flat_map(|c| c.subclasses())
+            .collect();
+        let interfaces: usize = subclasses.iter().map(|s| s.interfaces().len()).sum();
+        assert_eq!(pci_ids.vendors().len(), count(vendor_section, 0));
+        assert_eq!(pci_ids.all_devices().count(), count(vendor_section, 1));
+        assert_eq!(subdevices, count(vendor_section, 2));
+        assert_eq!(pci_ids.classes().len(), count(class_section, 0));
+        assert_eq!(subclasses.len(), count(class_section, 1));
+        assert_eq!(interfaces, count(class_section, 2));
+    }
+
+    /// Test that a gzip-compressed file parses to the same structure as the plain one
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_parse_gz_path() {
+        use std::io::Write;
+
+        let data = "# Version: 2024.01.01\n\
+                    10de  NVIDIA Corporation\n\
+                    \t1b80  GP104 [GeForce GTX 1080]\n\
+                    \t\t1043 8591  ROG STRIX GTX 1080\n\
+                    C 03  Display controller\n\
+                    \t00  VGA compatible controller\n";
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let dir = std::env::temp_dir().join(format!("pci-id-gz-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let gz_path = dir.join("pci.ids.data");
+        let plain_path = dir.join("pci.ids.gz");
+        std::fs::write(&gz_path, compressed).unwrap();
+        std::fs::write(&plain_path, data).unwrap();
+
+        let expected = format!("{:?}", PciIds::parse_from_str(data).unwrap());
+        assert_eq!(
+            format!("{:?}", PciIds::parse_gz_path(&gz_path).unwrap()),
+            expected
+        );
+        assert_eq!(
+            format!("{:?}", PciIds::parse_gz_path(&plain_path).unwrap()),
+            expected
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Test that the entry counts of the real file are within sane bounds
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_counts() {
+        assert!(PciIds::new().is_empty());
+        assert_eq!(PciIds::new().device_count(), 0);
+
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        assert!(!pci_ids.is_empty());
+        assert!(pci_ids.vendor_count() > 2000);
+        assert!(pci_ids.device_count() > pci_ids.vendor_count());
+        assert_eq!(pci_ids.device_count(), pci_ids.all_devices().count());
+        assert!(pci_ids.subdevice_count() > 10000);
+        assert!(pci_ids.class_count() > 20);
+        assert_eq!(pci_ids.class_count(), pci_ids.classes().len());
+    }
+
+    /// Test that the sorted vendor and device ids are ascending and one per entry
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_sorted_ids() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let vendor_ids = pci_ids.vendor_ids_sorted();
+        assert_eq!(vendor_ids.len(), pci_ids.vendor_count());
+        assert!(vendor_ids.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(pci_ids.vendor_ids()[0], pci_ids.vendors()[0].id());
+        assert_eq!(vendor_ids.last(), Some(&0xffff));
+
+        for vendor in pci_ids.vendors() {
+            let device_ids = vendor.device_ids_sorted();
+            assert_eq!(device_ids.len(), vendor.devices().len());
+            assert!(device_ids.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    /// Test that sorting orders every level by id and that binary search then agrees with the
+    /// linear lookup
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_sort() {
+        let data = "10de  NVIDIA Corporation\n\
+                    \t1b81  GP104 [GeForce GTX 1070]\n\
+                    \t\t1043 8599  ROG STRIX GTX 1070\n\
+                    \t\t1043 8591  ROG STRIX GTX 1070 OC\n\
+                    \t1b80  GP104 [GeForce GTX 1080]\n\
+                    1002  Advanced Micro Devices, Inc. [AMD/ATI]\n\
+                    C 0c  Serial bus controller\n\
+                    \t03  USB controller\n\
+                    \t\t30  XHCI\n\
+                    \t\t20  EHCI\n\
+                    C 03  Display controller\n";
+        let mut pci_ids = PciIds::parse_from_str(data).unwrap();
+        pci_ids.sort();
+        assert_eq!(pci_ids.vendors()[0].id(), 0x1002);
+        let nvidia = &pci_ids.vendors()[1];
+        assert_eq!(nvidia.devices()[0].id(), 0x1b80);
+        assert_eq!(nvidia.devices()[1].subdevices()[0].subdevice_id(), 0x8591);
+        assert_eq!(u8::from(pci_ids.classes()[0].class()), 0x03);
+        let usb = &pci_ids.classes()[1].subclasses()[0];
+        assert_eq!(usb.interfaces()[0].name(), "EHCI");
+
+        let mut pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        pci_ids.sort();
+        assert!(pci_ids.vendors().windows(2).all(|w| w[0].id() <= w[1].id()));
+        for vendor in pci_ids.vendors() {
+            assert!(vendor.devices().windows(2).all(|w| w[0].id() <= w[1].id()));
+        }
+        for id in (0..=u16::MAX)
+            .step_by(7)
+            .chain([0x0e11, 0x10de, 0x8086, 0xffff])
+        {
+            assert_eq!(
+                pci_ids.vendor_by_id_sorted(id).map(|v| v.id()),
+                pci_ids.vendor_by_id(id).map(|v| v.id())
+            );
+        }
+    }
+
+    /// Test that writing the real file out and parsing it again gives the same, sorted, data
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_to_pci_ids_string() {
+        let mut pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let written = pci_ids.to_pci_ids_string();
+        let reparsed = PciIds::parse_from_str(&written).unwrap();
+        pci_ids.sort();
+        assert_eq!(reparsed, pci_ids);
+        assert_eq!(reparsed.to_pci_ids_string(), written);
+        assert!(written.starts_with("# Version: "));
+
+        let options = ParseOptions {
+            keep_comments: true,
+            ..ParseOptions::default()
+        };
+        let data = "# Maintained by hand\n\
+                    0e11  Compaq Computer Corporation\n\
+                    # Seen in\n\
+                    #\n\
+                    # old servers\n\
+                    \t0001  PCI to EISA Bridge\n\
+                    \t\t0e11 b0f3  ProLiant\n\
+                    C 0c  Serial Bus Controller\n\
+                    \t03  USB controller\n\
+                    \t\t30  XHCI\n";
+        let pci_ids = PciIds::parse_with_options(data, options).unwrap();
+        assert_eq!(pci_ids.to_pci_ids_string(), data);
+    }
+
+    /// Test that an overlay renames an existing device and adds a new vendor, leaving the rest alone
+    #[test]
+    fn test_merge() {
+        let mut pci_ids = PciIds::parse_from_str(
+            "10de  NVIDIA Corporation\n\
+             \t1b80  GP104 [GeForce GTX 1080]\n\
+             \t1b81  GP104 [GeForce GTX 1070]\n",
+        )
+        .unwrap();
+        let overlay = PciIds::parse_from_str(
+            "10de  NVIDIA Corporation\n\
+             \t1b80  GP104 [GeForce GTX 1080 Custom]\n\
+             f00d  Example Proprietary Inc\n\
+             \t0001  Secret Accelerator\n",
+        )
+        .unwrap();
+        pci_ids.merge(overlay);
+
+        let names = pci_ids.resolve(0x10de, 0x1b80, None, None);
+        assert_eq!(names.device, Some("GP104 [GeForce GTX 1080 Custom]"));
+        let names = pci_ids.resolve(0x10de, 0x1b81, None, None);
+        assert_eq!(names.device, Some("GP104 [GeForce GTX 1070]"));
+        let names = pci_ids.resolve(0xf00d, 0x0001, None, None);
+        assert_eq!(names.vendor, Some("Example Proprietary Inc"));
+        assert_eq!(names.device, Some("Secret Accelerator"));
+        assert_eq!(pci_ids.vendor_count(), 2);
+        assert_eq!(pci_ids.device_count(), 3);
+    }
+
+    /// Test that duplicates, misordering and malformed lines are reported with their line numbers
+    #[test]
+    fn test_parse_with_diagnostics() {
+        let data = "10de  NVIDIA Corporation\n\
+                    \t1b81  GP104 [GeForce GTX 1070]\n\
+                    \t1b80  GP104 [GeForce GTX 1080]\n\
+                    0e11  Compaq Computer Corporation\n\
+                    10de  NVIDIA Corporation, again\n\
+                    1zz2  Broken\n\
+                    \t0001  Lost device\n\
+                    \t\t1043 8591  Lost subdevice\n\
+                    8086  Intel Corporation\n\
+                    \t0039  21145 Fast Ethernet\n";
+        let (pci_ids, diagnostics) = PciIds::parse_with_diagnostics(data);
+
+        let ids: Vec<u16> = pci_ids.vendors().iter().map(|v| v.id()).collect();
+        assert_eq!(ids, [0x10de, 0x0e11, 0x10de, 0x8086]);
+        assert_eq!(pci_ids.vendors()[2].devices().len(), 0);
+        assert_eq!(pci_ids.vendors()[3].devices()[0].id(), 0x0039);
+
+        assert_eq!(diagnostics.len(), 6);
+        assert!(matches!(
+            diagnostics[0],
+            Diagnostic::OutOfOrder {
+                line_number: 3,
+                previous_line_number: 2
+            }
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            Diagnostic::OutOfOrder {
+                line_number: 4,
+                previous_line_number: 1
+            }
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            Diagnostic::Duplicate {
+                line_number: 5,
+                first_line_number: 1
+            }
+        ));
+        assert!(matches!(
+            diagnostics[3],
+            Diagnostic::Skipped(ParseError::InvalidHex { line_number: 6, .. })
+        ));
+        assert!(matches!(
+            diagnostics[4],
+            Diagnostic::Skipped(ParseError::UnexpectedIndent { line_number: 7 })
+        ));
+        assert!(matches!(
+            diagnostics[5],
+            Diagnostic::Skipped(ParseError::UnexpectedIndent { line_number: 8 })
+        ));
+
+        let (clean, diagnostics) = PciIds::parse_with_diagnostics("0e11  Compaq\n\t0001  PCI\n");
+        assert_eq!(clean.vendors()[0].devices().len(), 1);
+        assert!(diagnostics.is_empty());
+    }
+
+    /// Test resolving the subvendor of a subdevice to the name of its vendor
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_subvendor_name() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let (_, _, subdevice) = pci_ids
+            .iter_subdevices()
+            .find(|(_, _, s)| s.subvendor_id() == 0x1da2)
+            .unwrap();
+        assert_eq!(
+            pci_ids.subvendor_name(subdevice),
+            Some("Sapphire Technology Limited")
+        );
+
+        let unknown = SubDevice::new(0x0abc, 0x0001, "Unknown card".to_owned());
+        assert_eq!(pci_ids.subvendor_name(&unknown), None);
+    }
+
+    /// Test that every subdevice is visited with its parents and can be filtered by subvendor
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_iter_subdevices() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        assert_eq!(pci_ids.iter_subdevices().count(), pci_ids.subdevice_count());
+
+        let sapphire: Vec<_> = pci_ids
+            .iter_subdevices()
+            .filter(|(_, _, s)| s.subvendor_id() == 0x1da2)
+            .collect();
+        assert!(!sapphire.is_empty());
+        for (vendor, device, subdevice) in sapphire {
+            assert!(std::ptr::eq(
+                vendor.device_by_id(device.id()).unwrap(),
+                device
+            ));
+            assert!(device.subdevices().contains(subdevice));
+        }
+    }
+
+    /// Test that the class side counts agree with iterating the class tree
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_class_side_counts() {
+        assert_eq!(PciIds::new().subclass_count(), 0);
+        assert_eq!(PciIds::new().interface_count(), 0);
+
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        assert!(pci_ids.interface_count() > 0);
+        assert_eq!(pci_ids.interface_count(), pci_ids.iter_interfaces().count());
+        assert!(pci_ids.subclass_count() > pci_ids.class_count());
+        assert_eq!(
+            pci_ids.subclass_count(),
+            pci_ids
+                .classes()
+                .iter()
+                .flat_map(|c| c.subclasses())
+                .count()
+        );
+        let serial_bus = pci_ids.class_by_id(0x0c).unwrap();
+        assert_eq!(serial_bus.subclass_count(), serial_bus.subclasses().len());
+
+        let mut classes_only = PciIds::new();
+        classes_only
+            .parse_classes(Path::new(PATH_TO_PCI_IDS))
+            .unwrap();
+        assert_eq!(classes_only.interface_count(), pci_ids.interface_count());
+        assert_eq!(classes_only.device_count(), 0);
+    }
+
+    /// Test that every programming interface is visited with its parents and can be searched
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_iter_interfaces() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let nvm: Vec<_> = pci_ids
+            .iter_interfaces()
+            .filter(|(_, _, i)| i.name().contains("NVM"))
+            .map(|(c, s, i)| (c.id(), s.id(), i.id(), i.name()))
+            .collect();
+        assert!(nvm.contains(&(0x01, 0x08, 0x02, "NVM Express")));
+        for (class, subclass, interface) in pci_ids.iter_interfaces() {
+            assert!(class.subclasses().contains(subclass));
+            assert!(subclass.interfaces().contains(interface));
+        }
+    }
+
+    /// Test the flat name tables against the real file
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_flat_maps() {
+        use std::collections::{BTreeSet, HashMap};
+
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let devices = HashMap::<(u16, u16), String>::from(&pci_ids);
+        assert_eq!(devices[&(0x8086, 0x0039)], "21145 Fast Ethernet");
+        let distinct: BTreeSet<(u16, u16)> = pci_ids
+            .all_devices()
+            .map(|(v, d)| (v.id(), d.id()))
+            .collect();
+        assert_eq!(devices.len(), distinct.len());
+
+        let vendors = HashMap::<u16, String>::from(&pci_ids);
+        assert_eq!(vendors[&0x8086], "Intel Corporation");
+        let distinct: BTreeSet<u16> = pci_ids.iter_vendors().map(|v| v.id()).collect();
+        assert_eq!(vendors.len(), distinct.len());
+    }
+
+    /// Test that interning the real file saves memory and keeps every name equal
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_intern() {
+        let original = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let mut interned = original.clone();
+        let before = interned.approximate_memory_usage();
+        let saved = interned.intern();
+        assert!(saved > 0);
+        assert_eq!(interned.approximate_memory_usage(), before - saved);
+        assert_eq!(interned, original);
+        assert_eq!(format!("{interned:?}"), format!("{original:?}"));
+        assert!(interned.names().eq(original.names()));
+    }
+
+    /// Test that parsing through a memory map gives the same result as reading the real file
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_parse_mmap() {
+        let mapped = PciIds::parse_mmap(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let read = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        assert_eq!(mapped, read);
+
+        let path = std::env::temp_dir().join(format!("pci-id-mmap-{}", std::process::id()));
+        std::fs::write(&path, b"10de  NVIDIA \xff\n").unwrap();
+        let mapped = PciIds::parse_mmap(&path).unwrap();
+        assert_eq!(mapped.vendors()[0].name(), "NVIDIA \u{fffd}");
+        std::fs::write(&path, b"").unwrap();
+        assert!(PciIds::parse_mmap(&path).unwrap().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Test that parsing only vendors stops at the class section but still completes the last
+    /// vendor before it, and isn't bothered by malformed class lines
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_vendors_stops_at_classes() {
+        let mut pci_ids = PciIds::new();
+        pci_ids.parse_vendors(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let last = pci_ids.vendors().last().unwrap();
+        assert_eq!((last.id(), last.name()), (0xffff, "Illegal Vendor ID"));
+        assert!(pci_ids.classes().is_empty());
+
+        let data = "10de  NVIDIA Corporation\n\
+                    1002  Advanced Micro Devices, Inc. [AMD/ATI]\n\
+                    \t731f  Navi 10\n\
+                    \t\t1043 04e2  ROG STRIX RX 5700 XT\n\
+                    C zz  Not a class\n\
+                    \t00  VGA compatible controller\n";
+        let mut pci_ids = PciIds::new();
+        pci_ids.parse_vendors_from_str(data).unwrap();
+        assert_eq!(pci_ids.vendor_count(), 2);
+        let amd = pci_ids.vendor_by_id(0x1002).unwrap();
+        assert_eq!(amd.devices().len(), 1);
+        assert_eq!(amd.devices()[0].subdevices().len(), 1);
+        assert!(PciIds::parse_from_str(data).is_err());
+    }
+
+    /// Test that trailing comments are split off names, and kept for vendors and devices along
+    /// with the comment lines above them
+    #[test]
+    fn test_inline_comments() {
+        let data = "0e11  Compaq Computer Corporation  # acquired by HP\n\
+                    # Tested on rev B\n\
+                    \t0046  Smart Array 64xx\t# driver: cciss\n\
+                    \t\t0e11 409a  Smart Array 641  # rare\n\
+                    \t0047  Controller #1\n\
+                    C 0c  Serial bus controller\n\
+                    \t03  USB controller  # also USB4\n\
+                    \t\t30  XHCI  # 3.x\n";
+        let options = ParseOptions {
+            keep_comments: true,
+            ..ParseOptions::default()
+        };
+        let pci_ids = PciIds::parse_with_options(data, options).unwrap();
+        let compaq = &pci_ids.vendors()[0];
+        assert_eq!(compaq.name(), "Compaq Computer Corporation");
+        assert_eq!(compaq.comment(), Some("acquired by HP"));
+        let array = &compaq.devices()[0];
+        assert_eq!(array.name(), "Smart Array 64xx");
+        assert_eq!(array.comment(), Some("Tested on rev B\ndriver: cciss"));
+        assert_eq!(array.subdevices()[0].name(), "Smart Array 641");
+        assert_eq!(array.subdevices()[0].comment(), Some("rare"));
+        assert_eq!(compaq.devices()[1].name(), "Controller #1");
+        assert_eq!(compaq.devices()[1].comment(), None);
+        let names = pci_ids.resolve_class(0x0c0330);
+        assert_eq!(names.subclass, Some("USB controller"));
+        assert_eq!(names.interface, Some("XHCI"));
+        let usb = pci_ids
+            .class_by_id(0x0c)
+            .unwrap()
+            .subclass_by_id(0x03)
+            .unwrap();
+        assert_eq!(usb.comment(), Some("also USB4"));
+        assert_eq!(usb.interface_by_id(0x30).unwrap().comment(), Some("3.x"));
+        let reparsed = PciIds::parse_with_options(&pci_ids.to_pci_ids_string(), options).unwrap();
+        assert_eq!(reparsed.classes(), pci_ids.classes());
+
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        assert_eq!(pci_ids.vendors()[0].name(), "Compaq Computer Corporation");
+        assert_eq!(pci_ids.vendors()[0].comment(), None);
+        assert_eq!(pci_ids.vendors()[0].devices()[0].name(), "Smart Array 64xx");
+        assert_eq!(
+            pci_ids.vendors()[0].devices()[0].subdevices()[0].comment(),
+            None
+        );
+    }
+
+    /// Test that a line with three tabs fails in strict mode and is skipped, along with the lines
+    /// below it, otherwise
+    #[test]
+    fn test_parse_strict() {
+        let data = "0e11  Compaq Computer Corporation\n\
+                    \t0046  Smart Array 64xx\n\
+                    \t\t\t0e11 409a  Too deep\n\
+                    \t0047  Next device\n\
+                    \x20\x200048  Space indented\n\
+                    \t\t0e11 409b  Below the skipped line\n\
+                    1002  Advanced Micro Devices, Inc. [AMD/ATI]\n";
+        assert!(ParseOptions::default().strict);
+        assert!(matches!(
+            PciIds::parse_with_options(data, ParseOptions::default()),
+            Err(ParseError::TooDeeplyNested {
+                line_number: 3,
+                depth: 3
+            })
+        ));
+
+        let options = ParseOptions {
+            strict: false,
+            ..ParseOptions::default()
+        };
+        let pci_ids = PciIds::parse_with_options(data, options).unwrap();
+        assert_eq!(pci_ids.vendor_count(), 2);
+        let compaq = &pci_ids.vendors()[0];
+        let devices: Vec<u16> = compaq.devices().iter().map(|d| d.id()).collect();
+        assert_eq!(devices, [0x0046, 0x0047]);
+        assert_eq!(pci_ids.subdevice_count(), 0);
+
+        // Other malformed lines still fail
+        let options = ParseOptions {
+            strict: false,
+            ..ParseOptions::default()
+        };
+        assert!(matches!(
+            PciIds::parse_with_options("0e11 Compaq\n", options),
+            Err(ParseError::MissingSeparator { line_number: 1 })
+        ));
+    }
+
+    /// Test that the database, vendors, devices and classes can be looped over directly
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_into_iterator() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+
+        let (mut vendors, mut devices, mut subdevices) = (0, 0, 0);
+        for vendor in &pci_ids {
+            vendors += 1;
+            for device in vendor {
+                devices += 1;
+                for subdevice in device {
+                    assert!(device.subdevices().contains(subdevice));
+                    subdevices += 1;
+                }
+            }
+        }
+        assert_eq!(vendors, pci_ids.vendor_count());
+        assert_eq!(devices, pci_ids.device_count());
+        assert_eq!(subdevices, pci_ids.subdevice_count());
+
+        let compaq = pci_ids.vendor_by_id(0x0e11).unwrap();
+        let first = compaq.into_iter().next().unwrap();
+        assert_eq!(first, &compaq.devices()[0]);
+
+        let mut subclasses = Vec::new();
+        for class in pci_ids.iter_classes() {
+            for subclass in class {
+                subclasses.push(subclass.name());
+            }
+        }
+        assert!(subclasses.contains(&"USB controller"));
+        assert_eq!(
+            subclasses.len(),
+            pci_ids
+                .classes()
+                .iter()
+                .map(|c| c.subclasses().len())
+                .sum::<usize>()
+        );
+    }
+
+    /// Test that selecting sections gives the same results as the single-purpose functions
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_selective() {
+        let path = Path::new(PATH_TO_PCI_IDS);
+        let mut vendors = PciIds::new();
+        vendors.parse_vendors(path).unwrap();
+        assert_eq!(PciIds::parse_selective(path, true, false).unwrap(), vendors);
+        assert!(vendors.classes().is_empty());
+
+        let mut classes = PciIds::new();
+        classes.parse_classes(path).unwrap();
+        assert_eq!(PciIds::parse_selective(path, false, true).unwrap(), classes);
+        assert!(classes.vendors().is_empty());
+
+        let all = PciIds::parse_selective(path, true, true).unwrap();
+        assert_eq!(all, PciIds::parse_pci_id_list(path).unwrap());
+        assert_eq!(all.vendors(), vendors.vendors());
+        assert_eq!(all.classes(), classes.classes());
+
+        let neither = PciIds::parse_selective(path, false, false).unwrap();
+        assert!(neither.is_empty());
+        assert_eq!(neither.version(), all.version());
+    }
+
+    /// Test that a pre-sized instance ends up the same as an empty one after parsing, and that
+    /// the parser's estimate covers the real file
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_with_capacity() {
+        use crate::pci_ids::estimate_capacity;
+
+        let data = std::fs::read_to_string(PATH_TO_PCI_IDS).unwrap();
+        let mut sized = PciIds::with_capacity(3000, 32);
+        assert!(sized.vendors.capacity() >= 3000);
+        sized.parse_vendors_from_str(&data).unwrap();
+        sized.parse_classes_from_str(&data).unwrap();
+        let mut empty = PciIds::new();
+        empty.parse_vendors_from_str(&data).unwrap();
+        empty.parse_classes_from_str(&data).unwrap();
+        assert_eq!(sized, empty);
+        assert_eq!(sized, PciIds::parse_from_str(&data).unwrap());
+
+        let (vendors, classes) = estimate_capacity(data.len());
+        assert!(vendors >= sized.vendor_count());
+        assert!(classes >= sized.class_count());
+        assert_eq!(estimate_capacity(100), (0, 0));
+
+        // A parse into empty lists with enough room fills them in place
+        let mut presized = PciIds::with_capacity(vendors, classes);
+        let (vendors_ptr, classes_ptr) = (presized.vendors.as_ptr(), presized.classes.as_ptr());
+        presized.parse_vendors_from_str(&data).unwrap();
+        presized.parse_classes_from_str(&data).unwrap();
+        assert_eq!(presized.vendors.as_ptr(), vendors_ptr);
+        assert_eq!(presized.classes.as_ptr(), classes_ptr);
+        assert_eq!(presized, empty);
+    }
+
+    /// Test that the cache is written on the first load, used while the modification time of the
+    /// source stays the same and replaced once it changes
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_load_cached() {
+        use std::fs;
+        use std::time::Duration;
+
+        let dir = std::env::temp_dir().join(format!("pci-id-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("pci.ids");
+        let cache = dir.join("pci.ids.bin");
+        fs::write(&source, "10de  NVIDIA Corporation\n").unwrap();
+
+        let first = PciIds::load_cached(&source, &cache).unwrap();
+        assert!(cache.exists());
+        assert_eq!(first.vendor_count(), 1);
+        assert!(first.vendor_by_id(0x10de).is_some());
+
+        // Only the modification time is checked, so the cache hides the new contents
+        let modified = fs::metadata(&source).unwrap().modified().unwrap();
+        fs::write(&source, "1002  Advanced Micro Devices, Inc. [AMD/ATI]\n").unwrap();
+        let file = fs::File::options().write(true).open(&source).unwrap();
+        file.set_modified(modified).unwrap();
+        assert_eq!(PciIds::load_cached(&source, &cache).unwrap(), first);
+
+        file.set_modified(modified + Duration::from_secs(1))
+            .unwrap();
+        let touched = PciIds::load_cached(&source, &cache).unwrap();
+        assert!(touched.vendor_by_id(0x1002).is_some());
+        assert_eq!(PciIds::load_cached(&source, &cache).unwrap(), touched);
+
+        fs::write(&cache, b"garbage").unwrap();
+        assert_eq!(PciIds::load_cached(&source, &cache).unwrap(), touched);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Test that an archive can be looked up in place and deserializes back to the same database
+    #[cfg(all(feature = "rkyv", feature = "std"))]
+    #[test]
+    fn test_archive_round_trip() {
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let bytes = pci_ids.archive_to_bytes().unwrap();
+
+        let archived = PciIds::from_archived_bytes(&bytes).unwrap();
+        assert_eq!(archived.vendors().len(), pci_ids.vendor_count());
+        let compaq = archived.vendor_by_id(0x0e11).unwrap();
+        assert_eq!(compaq.name(), "Compaq Computer Corporation");
+        let smart_array = compaq.device_by_id(0x0046).unwrap();
+        assert_eq!(smart_array.name(), "Smart Array 64xx");
+        let subdevice = smart_array
+            .subdevices()
+            .iter()
+            .find(|s| s.subvendor_id() == 0x0e11 && s.subdevice_id() == 0x409a)
+            .unwrap();
+        assert_eq!(subdevice.name(), "Smart Array 641");
+        assert!(archived.vendor_by_id(0x0abc).is_none());
+        let serial_bus = archived.class_by_id(0x0c).unwrap();
+        assert_eq!(serial_bus.class(), DeviceClass::SerialBusController);
+        assert_eq!(
+            serial_bus.subclass_by_id(0x03).unwrap().name(),
+            pci_ids
+                .class_by_id(0x0c)
+                .unwrap()
+                .subclass_by_id(0x03)
+                .unwrap()
+                .name()
+        );
+
+        let deserialized: PciIds = rkyv::deserialize::<_, rkyv::rancor::Error>(archived).unwrap();
+        assert_eq!(deserialized, pci_ids);
+
+        let mut garbage = rkyv::util::AlignedVec::<16>::new();
+        garbage.extend_from_slice(&[0xff; 64]);
+        assert!(PciIds::from_archived_bytes(&garbage).is_err());
+    }
+
+    /// Test that converting a string parses it like parse_from_str
+    #[test]
+    fn test_try_from_str() {
+        let data = "10de  NVIDIA Corporation\n\
+                    \t1b80  GP104 [GeForce GTX 1080]\n\
+                    C 03  Display controller\n\
+                    \t00  VGA compatible controller\n";
+        let pci_ids = PciIds::try_from(data).unwrap();
+        assert_eq!(pci_ids, PciIds::parse_from_str(data).unwrap());
+        assert_eq!(pci_ids.vendor_count(), 1);
+        assert_eq!(pci_ids.class_by_id(0x03).unwrap().subclasses().len(), 1);
+
+        let result: Result<PciIds, _> = "10de  NVIDIA Corporation\n  1b80  GP104\n".try_into();
+        assert!(matches!(
+            result,
+            Err(ParseError::UnexpectedIndent { line_number: 2 })
+        ));
+    }
+
+    /// Test parsing a file indented with two spaces per level through a custom classifier
+    #[test]
+    fn test_parse_with_classifier() {
+        use crate::pci_ids::{Classifier, Entry, LineClassifier};
+
+        #[derive(Default)]
+        struct TwoSpaces(Classifier);
+
+        impl LineClassifier for TwoSpaces {
+            fn classify<'a>(
+                &mut self,
+                line_number: usize,
+                line: &'a str,
+            ) -> Result<Option<Entry<'a>>, ParseError> {
+                let content = line.trim_start_matches(' ');
+                let indent = line.len() - content.len();
+                if !indent.is_multiple_of(2) {
+                    return Err(ParseError::UnexpectedIndent { line_number });
+                }
+                self.0.classify_at_depth(line_number, indent / 2, content)
+            }
+        }
+
+        let canonical = "# Comment\n\
+                         0e11  Compaq Computer Corporation\n\
+                         \t0046  Smart Array 64xx\n\
+                         \t\t0e11 409a  Smart Array 641\n\
+                         10de  NVIDIA Corporation\n\
+                         \t1b80  GP104 [GeForce GTX 1080]\n\
+                         C 01  Mass storage controller\n\
+                         \t06  SATA controller\n\
+                         \t\t01  AHCI 1.0\n";
+        let two_spaces = canonical.replace('\t', "  ");
+        let pci_ids = PciIds::parse_with_classifier(&two_spaces, TwoSpaces::default()).unwrap();
+        assert_eq!(pci_ids, PciIds::parse_from_str(canonical).unwrap());
+        assert_eq!(
+            pci_ids
+                .resolve(
+                    0x0e11,
+                    0x0046,
+                    Some(VendorId(0x0e11)),
+                    Some(SubDeviceId(0x409a))
+                )
+                .subdevice,
+            Some("Smart Array 641")
+        );
+
+        // The canonical classifier rejects the spaces, the custom one an odd indentation
+        assert!(matches!(
+            PciIds::parse_from_str(&two_spaces),
+            Err(ParseError::UnexpectedIndent { line_number: 3 })
+        ));
+        assert!(matches!(
+            PciIds::parse_with_classifier(
+                &two_spaces.replace("  0046", " 0046"),
+                TwoSpaces::default()
+            ),
+            Err(ParseError::UnexpectedIndent { line_number: 3 })
+        ));
+    }
+
+    /// Test that vendors are passed on one at a time and that breaking stops parsing
+    #[test]
+    fn test_for_each_vendor() {
+        use core::ops::ControlFlow;
+
+        let data = "# Comment\n\
+                    0e11  Compaq Computer Corporation\n\
+                    \t0046  Smart Array 64xx\n\
+                    \t\t0e11 409a  Smart Array 641\n\
+                    10de  NVIDIA Corporation\n\
+                    \t1b80  GP104 [GeForce GTX 1080]\n\
+                    1002  Advanced Micro Devices, Inc. [AMD/ATI]\n\
+                    \t\t\tmalformed\n";
+
+        let mut seen = Vec::new();
+        PciIds::for_each_vendor(data, |vendor| {
+            seen.push((vendor.id(), vendor.devices().len()));
+            if vendor.id() == 0x10de {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .unwrap();
+        assert_eq!(seen, [(0x0e11, 1), (0x10de, 1)]);
+
+        let mut compaq = None;
+        PciIds::for_each_vendor(data, |vendor| {
+            compaq.get_or_insert_with(|| vendor.clone());
+            ControlFlow::Continue(())
+        })
+        .unwrap_err();
+        let compaq = compaq.unwrap();
+        assert_eq!(
+            compaq.devices()[0].subdevices()[0].name(),
+            "Smart Array 641"
+        );
+
+        let mut last = None;
+        PciIds::for_each_vendor(&data[..data.rfind("\t\t\t").unwrap()], |vendor| {
+            last = Some(vendor.id());
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(last, Some(0x1002));
+    }
+
+    /// Test that parsing in parallel gives the same result as parsing sequentially, no matter how
+    /// small the chunks are
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parse_from_str_parallel() {
+        use crate::pci_ids::split_at_vendors;
+
+        let data = std::fs::read_to_string(PATH_TO_PCI_IDS).unwrap();
+        let sequential = PciIds::parse_from_str(&data).unwrap();
+        assert_eq!(PciIds::parse_from_str_parallel(&data).unwrap(), sequential);
+        assert_eq!(PciIds::parse_chunks(&data, 1).unwrap(), sequential);
+        assert_eq!(PciIds::parse_chunks(&data, usize::MAX).unwrap(), sequential);
+
+        let data = "# Version: 2024.01.01\n\
+                    10de  NVIDIA Corporation\n\
+                    \t1b80  GP104 [GeForce GTX 1080]\n\
+                    # Comment\n\
+                    1002  Advanced Micro Devices, Inc. [AMD/ATI]\n\
+                    \t731f  Navi 10\n\
+                    \t\t1043 04e2  ROG STRIX RX 5700 XT\n\
+                    8086  Intel Corporation\n\
+                    C 03  Display controller\n\
+                    \t00  VGA compatible controller\n";
+        let chunks = split_at_vendors(data, usize::MAX);
+        let starts: Vec<usize> = chunks.iter().map(|&(line_number, _)| line_number).collect();
+        assert_eq!(starts, [1, 8]);
+        assert_eq!(
+            chunks.iter().map(|&(_, chunk)| chunk).collect::<String>(),
+            data
+        );
+        assert_eq!(
+            PciIds::parse_chunks(data, usize::MAX).unwrap(),
+            PciIds::parse_from_str(data).unwrap()
+        );
+
+        // The first error of the file wins, with the line number counted from the start of it
+        for malformed in [
+            "10de  NVIDIA Corporation\n\t1b80 GP104\n1002  AMD\n\tzz  Not hex\n",
+            "10de  NVIDIA Corporation\n1002  AMD\n\tzz  Not hex\n",
+        ] {
+            assert_eq!(
+                format!("{:?}", PciIds::parse_chunks(malformed, usize::MAX)),
+                format!("{:?}", PciIds::parse_from_str(malformed))
+            );
+        }
+        assert!(matches!(
+            PciIds::parse_chunks("10de  NVIDIA Corporation\n1002  AMD\n\tzz  Not hex\n", 3),
+            Err(ParseError::InvalidHex { line_number: 3, .. })
+        ));
+    }
+
+    /// Test that parsing an in-memory file and resolving ids works with only `alloc`, as run by
+    /// `cargo test --no-default-features --features alloc`
+    #[test]
+    fn test_parse_alloc_only() {
+        let data = "# Version: 2024.01.01\n\
+                    10de  NVIDIA Corporation\n\
+                    \t1b80  GP104 [GeForce GTX 1080]\n\
+                    \t\t1043 8591  ROG STRIX GTX 1080\n\
+                    C 0c  Serial bus controller\n\
+                    \t03  USB controller\n\
+                    \t\t30  XHCI\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        assert_eq!(pci_ids.version(), Some("2024.01.01"));
+        let names = pci_ids.resolve(
+            0x10de,
+            0x1b80,
+            Some(VendorId(0x10de)),
+            Some(SubDeviceId(0x8591)),
+        );
+        assert_eq!(names.vendor, Some("NVIDIA Corporation"));
+        assert_eq!(names.device, Some("GP104 [GeForce GTX 1080]"));
+        assert_eq!(names.subdevice, None);
+        assert_eq!(pci_ids.resolve_class(0x0c0330).interface, Some("XHCI"));
+    }
+
+    /// Test that positions and vendors map onto each other
+    #[test]
+    fn test_vendor_at_and_index() {
+        let data = "0e11  Compaq Computer Corporation\n1234  Other Vendor\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        assert_eq!(pci_ids.vendor_index(0x1234), Some(1));
+        assert_eq!(pci_ids.vendor_at(1).unwrap().id(), 0x1234);
+        assert_eq!(pci_ids.vendor_index(0xabcd), None);
+        assert_eq!(pci_ids.vendor_at(2), None);
+    }
+
+    /// Test the display fallbacks for missing vendors and devices
+    #[test]
+    fn test_name_or_unknown() {
+        use alloc::borrow::Cow;
+
+        let data = "0e11  Compaq Computer Corporation\n\t0046  Smart Array 64xx\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        assert_eq!(
+            pci_ids.vendor_name_or_unknown(0x0e11),
+            "Compaq Computer Corporation"
+        );
+        assert_eq!(
+            pci_ids.vendor_name_or_unknown(0x00ab),
+            "Unknown vendor (00ab)"
+        );
+        assert_eq!(
+            pci_ids.device_name_or_unknown(0x0e11, 0x0046),
+            "Smart Array 64xx"
+        );
+        assert_eq!(
+            pci_ids.device_name_or_unknown(0x0e11, 0x0047),
+            "Unknown device (0047)"
+        );
+        assert_eq!(
+            pci_ids.device_name_or_unknown(0x00ab, 0x0046),
+            "Unknown device (0046)"
+        );
+        assert!(matches!(
+            pci_ids.vendor_name_or_unknown(0x0e11),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            pci_ids.device_name_or_unknown(0x0e11, 0x0047),
+            Cow::Owned(_)
+        ));
+    }
+
+    /// Test that subvendor ids are split up by whether they have a vendor, counting each id once
+    #[test]
+    fn test_subvendor_coverage() {
+        let data = "0e11  Compaq Computer Corporation\n\
+                    \t0046  Smart Array 64xx\n\
+                    \t\t0e11 409a  Smart Array 641\n\
+                    \t\t0e11 409b  Smart Array 642\n\
+                    \t\tabcd 0001  Unknown\n\
+                    \t\t1234 0002  Unknown\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        let coverage = pci_ids.subvendor_coverage();
+        assert_eq!(coverage.resolved(), 1);
+        assert_eq!(coverage.unresolved(), 2);
+        assert_eq!(coverage.unresolved_ids(), &[0x1234, 0xabcd]);
     }
 }
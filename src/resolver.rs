@@ -0,0 +1,129 @@
+//! Memoized lookups for resolving the same ids over and over, e.g. once per frame in a UI.
+
+use std::collections::HashMap;
+
+use crate::ids::{SubDeviceId, VendorId};
+use crate::pci_ids::{PciIds, ResolvedNames};
+use crate::vendor::{Device, Vendor};
+
+/// Wrapper around a [PciIds] that remembers the result of every lookup it has done.
+///
+/// Misses are remembered as well, so repeatedly resolving an unknown device is just as cheap.
+/// Created with [PciIds::cached_resolver].
+#[derive(Debug, Clone)]
+pub struct CachedResolver<'a> {
+    pci_ids: &'a PciIds,
+    vendors: HashMap<u16, Option<&'a Vendor>>,
+    devices: HashMap<(u16, u16), Option<(&'a Vendor, &'a Device)>>,
+    names: HashMap<(u16, u16, Option<u16>, Option<u16>), ResolvedNames<'a>>,
+}
+
+impl<'a> CachedResolver<'a> {
+    /// Create a new resolver with an empty cache.
+    pub fn new(pci_ids: &'a PciIds) -> Self {
+        Self {
+            pci_ids,
+            vendors: HashMap::new(),
+            devices: HashMap::new(),
+            names: HashMap::new(),
+        }
+    }
+
+    /// The database lookups are done against.
+    pub fn pci_ids(&self) -> &'a PciIds {
+        self.pci_ids
+    }
+
+    /// Look up the vendor with the given id.
+    pub fn resolve_vendor(&mut self, vendor: u16) -> Option<&'a Vendor> {
+        let pci_ids = self.pci_ids;
+        *self
+            .vendors
+            .entry(vendor)
+            .or_insert_with(|| pci_ids.vendor_by_id(vendor))
+    }
+
+    /// Look up the given vendor's device, along with the vendor.
+    pub fn resolve_device(&mut self, vendor: u16, device: u16) -> Option<(&'a Vendor, &'a Device)> {
+        if let Some(&resolved) = self.devices.get(&(vendor, device)) {
+            return resolved;
+        }
+        let resolved = self
+            .resolve_vendor(vendor)
+            .and_then(|v| v.device_by_id(device).map(|d| (v, d)));
+        self.devices.insert((vendor, device), resolved);
+        resolved
+    }
+
+    /// Resolve the names for the four ids identifying a device, like [PciIds::resolve].
+    pub fn resolve(
+        &mut self,
+        vendor: u16,
+        device: u16,
+        subvendor: Option<VendorId>,
+        subdevice: Option<SubDeviceId>,
+    ) -> ResolvedNames<'a> {
+        let pci_ids = self.pci_ids;
+        let key = (
+            vendor,
+            device,
+            subvendor.map(u16::from),
+            subdevice.map(u16::from),
+        );
+        *self
+            .names
+            .entry(key)
+            .or_insert_with(|| pci_ids.resolve(vendor, device, subvendor, subdevice))
+    }
+
+    /// Forget every remembered lookup.
+    pub fn clear(&mut self) {
+        self.vendors.clear();
+        self.devices.clear();
+        self.names.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ids::{SubDeviceId, VendorId};
+    use crate::pci_ids::PciIds;
+
+    /// Test that lookups give the same answers as the database and are remembered, misses included
+    #[test]
+    fn test_cached_resolver() {
+        let data = "10de  NVIDIA Corporation\n\
+                    \t1b80  GP104 [GeForce GTX 1080]\n\
+                    \t\t1043 8591  ROG STRIX GTX 1080\n\
+                    1043  ASUSTeK Computer Inc.\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        let mut resolver = pci_ids.cached_resolver();
+
+        for _ in 0..2 {
+            let (vendor, device) = resolver.resolve_device(0x10de, 0x1b80).unwrap();
+            assert!(std::ptr::eq(vendor, &pci_ids.vendors()[0]));
+            assert!(std::ptr::eq(device, &pci_ids.vendors()[0].devices()[0]));
+            assert!(resolver.resolve_device(0x10de, 0x1b81).is_none());
+            assert!(resolver.resolve_vendor(0x1002).is_none());
+
+            let asus = Some(VendorId(0x1043));
+            let names = resolver.resolve(0x10de, 0x1b80, asus, Some(SubDeviceId(0x8591)));
+            assert_eq!(
+                names,
+                pci_ids.resolve(0x10de, 0x1b80, asus, Some(SubDeviceId(0x8591)))
+            );
+            assert_eq!(names.subdevice, Some("ROG STRIX GTX 1080"));
+            let names = resolver.resolve(0x10de, 0x1b80, asus, Some(SubDeviceId(0x8592)));
+            assert_eq!(names.subvendor, Some("ASUSTeK Computer Inc."));
+            assert_eq!(names.subdevice, None);
+        }
+        assert_eq!(resolver.devices.len(), 2);
+        assert_eq!(resolver.vendors.len(), 2);
+        assert_eq!(resolver.names.len(), 2);
+
+        resolver.clear();
+        assert!(resolver.devices.is_empty());
+        assert!(resolver.names.is_empty());
+        assert!(resolver.resolve_vendor(0x10de).is_some());
+    }
+}
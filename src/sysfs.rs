@@ -0,0 +1,230 @@
+//! Enumeration of the PCI devices present in the running system through Linux sysfs.
+//!
+//! ```no_run
+//! use std::path::Path;
+//! use pci_id::pci_ids::{PciIds, PATH_TO_PCI_IDS};
+//!
+//! let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+//! for device in pci_id::sysfs::enumerate(&pci_ids).unwrap() {
+//!     println!("{} {:?}", device.address, device.names.device);
+//! }
+//! ```
+
+use std::path::Path;
+use std::{fs, io};
+
+use crate::address::PciAddress;
+use crate::ids::{SubDeviceId, VendorId};
+use crate::pci_ids::{parse_hex_u16, PciIds, ResolvedClass, ResolvedNames};
+use crate::vendor::SubsystemId;
+
+/// Directory holding one subdirectory per PCI device of the running system.
+pub(crate) const PATH_TO_SYSFS_DEVICES: &str = "/sys/bus/pci/devices";
+
+/// A PCI device of the running system along with its names in the database.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct LiveDevice<'a> {
+    /// Address of the device, e.g. `0000:03:00.0`.
+    pub address: PciAddress,
+    /// Id of the vendor.
+    pub vendor_id: u16,
+    /// Id of the device.
+    pub device_id: u16,
+    /// Ids of the subsystem, missing for devices that don't report one, like some bridges.
+    pub subsystem: Option<SubsystemId>,
+    /// Class code of the device, like `0x0c0330`.
+    pub class_code: Option<u32>,
+    /// Names of the device and its subsystem.
+    pub names: ResolvedNames<'a>,
+    /// Names of the class code, all `None` if there is no class code.
+    pub class_names: ResolvedClass<'a>,
+}
+
+/// Read every PCI device of the running system from `/sys/bus/pci/devices` and resolve its
+/// names against `db`. Devices are sorted by address.
+///
+/// # Errors
+/// Fails if a device directory can not be read, isn't named after a PCI address or holds an
+/// invalid vendor or device id.
+pub fn enumerate(db: &PciIds) -> io::Result<Vec<LiveDevice<'_>>> {
+    enumerate_in(db, Path::new(PATH_TO_SYSFS_DEVICES))
+}
+
+/// Like [enumerate], but for the device directories below `root`.
+fn enumerate_in<'a>(db: &'a PciIds, root: &Path) -> io::Result<Vec<LiveDevice<'a>>> {
+    let mut devices = Vec::new();
+    for device in read_devices(root)? {
+        let address = device.address.parse().map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid device address {}", device.address),
+            )
+        })?;
+        devices.push(LiveDevice {
+            names: db.resolve(
+                device.vendor_id,
+                device.device_id,
+                device.subsystem.map(|id| VendorId(id.subvendor_id())),
+                device.subsystem.map(|id| SubDeviceId(id.subdevice_id())),
+            ),
+            class_names: device
+                .class_code
+                .map(|code| db.resolve_class(code))
+                .unwrap_or_default(),
+            address,
+            vendor_id: device.vendor_id,
+            device_id: device.device_id,
+            subsystem: device.subsystem,
+            class_code: device.class_code,
+        });
+    }
+    devices.sort_by_key(|d| d.address);
+    Ok(devices)
+}
+
+/// Ids of a single PCI device as reported by sysfs.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub(crate) struct SystemDevice {
+    /// Name of the device directory, which is the address of the device.
+    pub(crate) address: String,
+    pub(crate) vendor_id: u16,
+    pub(crate) device_id: u16,
+    /// Missing for devices that don't report a subsystem, like some bridges.
+    pub(crate) subsystem: Option<SubsystemId>,
+    /// Missing if the class file can't be read.
+    pub(crate) class_code: Option<u32>,
+}
+
+/// Read the ids of every device below the given sysfs directory, e.g. [PATH_TO_SYSFS_DEVICES].
+pub(crate) fn read_devices(root: &Path) -> io::Result<Vec<SystemDevice>> {
+    let mut devices = Vec::new();
+    for entry in fs::read_dir(root)? {
+        let entry = entry?;
+        let dir = entry.path();
+        let subsystem = match (
+            read_id(&dir, "subsystem_vendor"),
+            read_id(&dir, "subsystem_device"),
+        ) {
+            (Ok(subvendor_id), Ok(subdevice_id)) => {
+                Some(SubsystemId::new(subvendor_id, subdevice_id))
+            }
+            _ => None,
+        };
+        devices.push(SystemDevice {
+            address: entry.file_name().to_string_lossy().into_owned(),
+            vendor_id: read_id(&dir, "vendor")?,
+            device_id: read_id(&dir, "device")?,
+            subsystem,
+            class_code: read_class_code(&dir).ok(),
+        });
+    }
+    Ok(devices)
+}
+
+/// Read a file holding a single hexadecimal id like `0x8086`.
+fn read_id(dir: &Path, file: &str) -> io::Result<u16> {
+    let contents = fs::read_to_string(dir.join(file))?;
+    parse_hex_u16(&contents)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("invalid id in {file}")))
+}
+
+/// Read the `class` file, holding a class code like `0x0c0330`.
+fn read_class_code(dir: &Path) -> io::Result<u32> {
+    let contents = fs::read_to_string(dir.join("class"))?;
+    let trimmed = contents.trim();
+    let digits = trimmed.strip_prefix("0x").unwrap_or(trimmed);
+    u32::from_str_radix(digits, 16)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid class code in class"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{enumerate, enumerate_in, read_devices, SystemDevice};
+    use crate::pci_ids::{PciIds, PATH_TO_PCI_IDS};
+    use crate::vendor::SubsystemId;
+    use std::fs;
+    use std::path::Path;
+
+    /// Test that device directories are read, with or without a subsystem
+    #[test]
+    fn test_read_devices() {
+        let root = std::env::temp_dir().join(format!("pci-id-sysfs-{}", std::process::id()));
+        let with_subsystem = root.join("0000:03:00.0");
+        let without_subsystem = root.join("0000:00:01.0");
+        fs::create_dir_all(&with_subsystem).unwrap();
+        fs::create_dir_all(&without_subsystem).unwrap();
+        fs::write(with_subsystem.join("vendor"), "0x0e11\n").unwrap();
+        fs::write(with_subsystem.join("device"), "0x0046\n").unwrap();
+        fs::write(with_subsystem.join("subsystem_vendor"), "0x0e11\n").unwrap();
+        fs::write(with_subsystem.join("subsystem_device"), "0x409a\n").unwrap();
+        fs::write(with_subsystem.join("class"), "0x010400\n").unwrap();
+        fs::write(without_subsystem.join("vendor"), "0x8086\n").unwrap();
+        fs::write(without_subsystem.join("device"), "0x1901\n").unwrap();
+
+        let mut devices = read_devices(&root).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        devices.sort_by_key(|d| d.vendor_id);
+        assert_eq!(
+            devices,
+            [
+                SystemDevice {
+                    address: "0000:03:00.0".to_owned(),
+                    vendor_id: 0x0e11,
+                    device_id: 0x0046,
+                    subsystem: Some(SubsystemId::new(0x0e11, 0x409a)),
+                    class_code: Some(0x010400),
+                },
+                SystemDevice {
+                    address: "0000:00:01.0".to_owned(),
+                    vendor_id: 0x8086,
+                    device_id: 0x1901,
+                    subsystem: None,
+                    class_code: None,
+                },
+            ]
+        );
+    }
+
+    /// Test that enumerated devices are sorted by address and resolved against the database
+    #[test]
+    fn test_enumerate_in() {
+        let root = std::env::temp_dir().join(format!("pci-id-enumerate-{}", std::process::id()));
+        let raid = root.join("0000:03:00.0");
+        let bridge = root.join("0000:00:01.0");
+        fs::create_dir_all(&raid).unwrap();
+        fs::create_dir_all(&bridge).unwrap();
+        fs::write(raid.join("vendor"), "0x0e11\n").unwrap();
+        fs::write(raid.join("device"), "0x0046\n").unwrap();
+        fs::write(raid.join("subsystem_vendor"), "0x0e11\n").unwrap();
+        fs::write(raid.join("subsystem_device"), "0x409a\n").unwrap();
+        fs::write(raid.join("class"), "0x010400\n").unwrap();
+        fs::write(bridge.join("vendor"), "0x0abc\n").unwrap();
+        fs::write(bridge.join("device"), "0x0001\n").unwrap();
+        fs::write(bridge.join("class"), "0x060400\n").unwrap();
+
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let devices = enumerate_in(&pci_ids, &root).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].address.to_string(), "0000:00:01.0");
+        assert_eq!(devices[0].names.vendor, None);
+        assert_eq!(devices[0].class_names.subclass, Some("PCI bridge"));
+        assert_eq!(devices[1].address.bus, 0x03);
+        assert_eq!(devices[1].names.device, Some("Smart Array 64xx"));
+        assert_eq!(devices[1].names.subdevice, Some("Smart Array 641"));
+        assert_eq!(devices[1].class_names.subclass, Some("RAID bus controller"));
+    }
+
+    /// Test that the running system can be enumerated if it exposes sysfs
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_enumerate() {
+        if !Path::new("/sys/bus/pci").exists() {
+            return;
+        }
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let devices = enumerate(&pci_ids).unwrap();
+        assert!(devices.windows(2).all(|w| w[0].address < w[1].address));
+    }
+}
@@ -0,0 +1,162 @@
+//! The bare id structure of a pci.ids file, without any of the names.
+//!
+//! Handy for checking which ids exist at all, e.g. when building a validity bitmap, without paying
+//! for the tens of thousands of name allocations the full [PciIds](crate::pci_ids::PciIds) needs.
+
+use alloc::vec::Vec;
+
+use crate::pci_ids::{Classifier, Entry, LineClassifier, ParseError};
+
+/// Every id from a pci.ids file along with its position in the hierarchy.
+///
+/// Each level is keyed by the ids of its parents, so a device is identified by
+/// `(vendor, device)` and a subdevice by `(vendor, device, subvendor, subdevice)`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdTopology {
+    vendors: Vec<u16>,
+    devices: Vec<(u16, u16)>,
+    subdevices: Vec<(u16, u16, u16, u16)>,
+    classes: Vec<u8>,
+    subclasses: Vec<(u8, u8)>,
+    interfaces: Vec<(u8, u8, u8)>,
+}
+
+impl IdTopology {
+    /// Parse the ids from the contents of a pci.ids file.
+    pub(crate) fn parse(data: &str) -> Result<Self, ParseError> {
+        let mut topology = Self::default();
+        let mut classifier = Classifier::default();
+        let (mut vendor, mut device, mut class, mut subclass) = (None, None, None, None);
+
+        for (index, line) in data.lines().enumerate() {
+            let Some(entry) = classifier.classify(index + 1, line)? else {
+                continue;
+            };
+            match entry {
+                Entry::Comment(_) => {}
+                Entry::Vendor { id, .. } => {
+                    topology.vendors.push(id);
+                    vendor = Some(id);
+                    device = None;
+                }
+                Entry::Device { id, .. } => {
+                    if let Some(v) = vendor {
+                        topology.devices.push((v, id));
+                        device = Some(id);
+                    }
+                }
+                Entry::SubDevice {
+                    subvendor_id,
+                    subdevice_id,
+                    ..
+                } => {
+                    if let (Some(v), Some(d)) = (vendor, device) {
+                        topology.subdevices.push((v, d, subvendor_id, subdevice_id));
+                    }
+                }
+                Entry::Class { id, .. } => {
+                    topology.classes.push(id);
+                    class = Some(id);
+                    subclass = None;
+                }
+                Entry::SubClass { id, .. } => {
+                    if let Some(c) = class {
+                        topology.subclasses.push((c, id));
+                        subclass = Some(id);
+                    }
+                }
+                Entry::Interface { id, .. } => {
+                    if let (Some(c), Some(s)) = (class, subclass) {
+                        topology.interfaces.push((c, s, id));
+                    }
+                }
+            }
+        }
+
+        // Sorted so lookups can binary search
+        topology.vendors.sort_unstable();
+        topology.vendors.dedup();
+        topology.devices.sort_unstable();
+        topology.devices.dedup();
+        topology.subdevices.sort_unstable();
+        topology.subdevices.dedup();
+        topology.classes.sort_unstable();
+        topology.classes.dedup();
+        topology.subclasses.sort_unstable();
+        topology.subclasses.dedup();
+        topology.interfaces.sort_unstable();
+        topology.interfaces.dedup();
+        Ok(topology)
+    }
+
+    /// Whether a vendor with the given id exists.
+    pub fn contains_vendor(&self, vendor: u16) -> bool {
+        self.vendors.binary_search(&vendor).is_ok()
+    }
+
+    /// Whether the vendor has a device with the given id.
+    pub fn contains_device(&self, vendor: u16, device: u16) -> bool {
+        self.devices.binary_search(&(vendor, device)).is_ok()
+    }
+
+    /// Whether the device has a subdevice with the given subvendor and subdevice ids.
+    pub fn contains_subdevice(
+        &self,
+        vendor: u16,
+        device: u16,
+        subvendor: u16,
+        subdevice: u16,
+    ) -> bool {
+        self.subdevices
+            .binary_search(&(vendor, device, subvendor, subdevice))
+            .is_ok()
+    }
+
+    /// Whether a class with the given id exists.
+    pub fn contains_class(&self, class: u8) -> bool {
+        self.classes.binary_search(&class).is_ok()
+    }
+
+    /// Whether the class has a subclass with the given id.
+    pub fn contains_subclass(&self, class: u8, subclass: u8) -> bool {
+        self.subclasses.binary_search(&(class, subclass)).is_ok()
+    }
+
+    /// Whether the subclass has a programming interface with the given id.
+    pub fn contains_interface(&self, class: u8, subclass: u8, interface: u8) -> bool {
+        self.interfaces
+            .binary_search(&(class, subclass, interface))
+            .is_ok()
+    }
+
+    /// All vendor ids in ascending order.
+    pub fn vendors(&self) -> &[u16] {
+        &self.vendors
+    }
+
+    /// All `(vendor, device)` id pairs in ascending order.
+    pub fn devices(&self) -> &[(u16, u16)] {
+        &self.devices
+    }
+
+    /// All `(vendor, device, subvendor, subdevice)` ids in ascending order.
+    pub fn subdevices(&self) -> &[(u16, u16, u16, u16)] {
+        &self.subdevices
+    }
+
+    /// All class ids in ascending order.
+    pub fn classes(&self) -> &[u8] {
+        &self.classes
+    }
+
+    /// All `(class, subclass)` id pairs in ascending order.
+    pub fn subclasses(&self) -> &[(u8, u8)] {
+        &self.subclasses
+    }
+
+    /// All `(class, subclass, interface)` ids in ascending order.
+    pub fn interfaces(&self) -> &[(u8, u8, u8)] {
+        &self.interfaces
+    }
+}
@@ -0,0 +1,328 @@
+//! Fetching the latest pci.ids from the internet.
+//!
+//! ```no_run
+//! use pci_id::pci_ids::PciIds;
+//!
+//! let data = pci_id::update::download(None).unwrap();
+//! let pci_ids = PciIds::parse_from_str(&data).unwrap();
+//! ```
+
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use std::{fmt, fs};
+
+use crate::pci_ids::{ParseError, PciIds};
+
+/// Canonical location of the pci.ids file, used when no other URL is given.
+pub const DEFAULT_URL: &str = "https://pci-ids.ucw.cz/v2.2/pci.ids";
+
+/// Errors that can occur while downloading a pci.ids file.
+#[derive(Debug)]
+pub enum UpdateError {
+    /// The server couldn't be reached or the connection failed.
+    Transport(Box<ureq::Transport>),
+    /// The server answered with a status other than 200 OK.
+    Status(u16),
+    /// Reading the response body failed, e.g. because it wasn't valid UTF-8.
+    Io(io::Error),
+    /// The downloaded file is malformed.
+    Parse(ParseError),
+}
+
+impl fmt::Display for UpdateError {
+    /// Describe the error, underlying errors are left to [source](std::error::Error::source).
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Transport(_) => f.write_str("failed to reach the server"),
+            Self::Status(status) => write!(f, "server answered with status {status}"),
+            Self::Io(_) => f.write_str("failed to read or store the downloaded file"),
+            Self::Parse(_) => f.write_str("downloaded file is malformed"),
+        }
+    }
+}
+
+impl std::error::Error for UpdateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(err) => Some(err),
+            Self::Status(_) => None,
+            Self::Io(err) => Some(err),
+            Self::Parse(err) => Some(err),
+        }
+    }
+}
+
+impl From<ureq::Error> for UpdateError {
+    fn from(err: ureq::Error) -> Self {
+        match err {
+            ureq::Error::Status(status, _) => Self::Status(status),
+            ureq::Error::Transport(transport) => Self::Transport(Box::new(transport)),
+        }
+    }
+}
+
+impl From<io::Error> for UpdateError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ParseError> for UpdateError {
+    fn from(err: ParseError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+/// Download a pci.ids file, from [DEFAULT_URL] unless another `url` is given.
+///
+/// The body is returned as is, ready for [PciIds::parse_from_str](crate::pci_ids::PciIds::parse_from_str).
+pub fn download(url: Option<&str>) -> Result<String, UpdateError> {
+    let response = ureq::get(url.unwrap_or(DEFAULT_URL)).call()?;
+    if response.status() != 200 {
+        return Err(UpdateError::Status(response.status()));
+    }
+    let mut body = String::new();
+    response.into_reader().read_to_string(&mut body)?;
+    Ok(body)
+}
+
+/// Download a pci.ids file like [download] and store it at `dest`.
+///
+/// The file is first written next to `dest` and then renamed into place, so a failed or
+/// interrupted download never leaves a truncated file behind. If `dest` already exists its
+/// permissions are kept.
+pub fn download_to_path(url: Option<&str>, dest: &Path) -> Result<(), UpdateError> {
+    let body = download(url)?;
+    Ok(store(&body, dest)?)
+}
+
+/// Parse the file at `cache` if it was modified less than `max_age` ago, otherwise download a
+/// fresh copy like [download], store it at `cache` and parse that.
+///
+/// A cache that is fresh but can't be read or parsed is downloaded again as well. A download that
+/// doesn't parse is not stored.
+pub(crate) fn load_or_update(
+    url: Option<&str>,
+    cache: &Path,
+    max_age: Duration,
+) -> Result<PciIds, UpdateError> {
+    let modified = fs::metadata(cache).and_then(|metadata| metadata.modified());
+    if let Ok(modified) = modified {
+        // A modification time in the future counts as fresh
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if age < max_age {
+            if let Ok(pci_ids) = PciIds::parse_pci_id_list(cache) {
+                return Ok(pci_ids);
+            }
+        }
+    }
+
+    let body = download(url)?;
+    let pci_ids = PciIds::parse_from_str(&body)?;
+    store(&body, cache)?;
+    Ok(pci_ids)
+}
+
+/// Store `body` at `dest` through a temporary file next to it, see [download_to_path].
+fn store(body: &str, dest: &Path) -> io::Result<()> {
+    let file_name = dest.file_name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "destination has no file name")
+    })?;
+    let mut temp_name = file_name.to_owned();
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = dest.with_file_name(temp_name);
+
+    let result = write_replacing(&temp_path, dest, body.as_bytes());
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Write `contents` to `temp_path` and rename it over `dest`.
+fn write_replacing(temp_path: &Path, dest: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = fs::File::create(temp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    match fs::metadata(dest) {
+        Ok(metadata) => file.set_permissions(metadata.permissions())?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+    drop(file);
+    fs::rename(temp_path, dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use crate::pci_ids::PciIds;
+    use crate::update::{download, download_to_path, load_or_update, UpdateError};
+
+    /// Serve a single request with the given status line and body, returning the URL to fetch.
+    fn serve_once(status: &'static str, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        });
+        format!("http://{address}/pci.ids")
+    }
+
+    /// Test that a successful response is returned verbatim and parses
+    #[test]
+    fn test_download() {
+        let body = "10de  NVIDIA Corporation\n\t1b80  GP104 [GeForce GTX 1080]\n";
+        let data = download(Some(&serve_once("200 OK", body))).unwrap();
+        assert_eq!(data, body);
+        let pci_ids = PciIds::parse_from_str(&data).unwrap();
+        assert_eq!(
+            pci_ids.vendors()[0].devices()[0].name(),
+            "GP104 [GeForce GTX 1080]"
+        );
+    }
+
+    /// Test that non-200 responses and unreachable servers are reported as errors
+    #[test]
+    fn test_download_errors() {
+        let url = serve_once("404 Not Found", "not here");
+        assert!(matches!(
+            download(Some(&url)),
+            Err(UpdateError::Status(404))
+        ));
+        let url = serve_once("204 No Content", "");
+        assert!(matches!(
+            download(Some(&url)),
+            Err(UpdateError::Status(204))
+        ));
+
+        let unused = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let result = download(Some(&format!("http://{unused}/pci.ids")));
+        assert!(matches!(result, Err(UpdateError::Transport(_))));
+
+        let err = result.unwrap_err();
+        assert_eq!(err.to_string(), "failed to reach the server");
+        assert!(std::error::Error::source(&err).is_some());
+        assert_eq!(
+            UpdateError::Status(404).to_string(),
+            "server answered with status 404"
+        );
+    }
+
+    /// Test that the destination is replaced in one go, keeping its permissions, and left alone on errors
+    #[test]
+    fn test_download_to_path() {
+        let dir = std::env::temp_dir().join(format!("pci-id-update-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let dest = dir.join("pci.ids");
+        fs::write(&dest, "old").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&dest, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+
+        let url = serve_once("500 Internal Server Error", "");
+        assert!(matches!(
+            download_to_path(Some(&url), &dest),
+            Err(UpdateError::Status(500))
+        ));
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "old");
+
+        let body = "10de  NVIDIA Corporation\n";
+        download_to_path(Some(&serve_once("200 OK", body)), &dest).unwrap();
+        assert_eq!(fs::read_to_string(&dest).unwrap(), body);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&dest).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        let fresh = dir.join("fresh.ids");
+        download_to_path(Some(&serve_once("200 OK", body)), &fresh).unwrap();
+        assert_eq!(fs::read_to_string(&fresh).unwrap(), body);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Test that a fresh cache is used as is, while a stale or missing one is downloaded again
+    #[test]
+    fn test_load_or_update() {
+        use std::time::{Duration, SystemTime};
+
+        let dir = std::env::temp_dir().join(format!("pci-id-load-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let cache = dir.join("pci.ids");
+        let max_age = Duration::from_secs(60 * 60);
+        let unreachable = format!(
+            "http://{}/pci.ids",
+            TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap()
+        );
+
+        // Fresh, so nothing is downloaded
+        fs::write(&cache, "10de  NVIDIA Corporation\n").unwrap();
+        let pci_ids = load_or_update(Some(&unreachable), &cache, max_age).unwrap();
+        assert!(pci_ids.vendor_by_id(0x10de).is_some());
+
+        // Expired, so the cache is replaced by the download
+        let body = "1002  Advanced Micro Devices, Inc. [AMD/ATI]\n";
+        let file = fs::File::options().write(true).open(&cache).unwrap();
+        file.set_modified(SystemTime::now() - 2 * max_age).unwrap();
+        drop(file);
+        assert!(matches!(
+            load_or_update(Some(&unreachable), &cache, max_age),
+            Err(UpdateError::Transport(_))
+        ));
+        let pci_ids = load_or_update(Some(&serve_once("200 OK", body)), &cache, max_age).unwrap();
+        assert!(pci_ids.vendor_by_id(0x1002).is_some());
+        assert_eq!(fs::read_to_string(&cache).unwrap(), body);
+        let pci_ids = load_or_update(Some(&unreachable), &cache, max_age).unwrap();
+        assert!(pci_ids.vendor_by_id(0x1002).is_some());
+
+        // Missing, and a malformed download isn't stored
+        fs::remove_file(&cache).unwrap();
+        let url = serve_once("200 OK", "10de  NVIDIA Corporation\n  1b80  GP104\n");
+        assert!(matches!(
+            load_or_update(Some(&url), &cache, max_age),
+            Err(UpdateError::Parse(_))
+        ));
+        assert!(!cache.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Test downloading and parsing the real file, needs network access
+    #[test]
+    #[ignore]
+    fn test_download_default_url() {
+        let data = download(None).unwrap();
+        let pci_ids = PciIds::parse_from_str(&data).unwrap();
+        assert!(pci_ids.vendor_by_id(0x10de).is_some());
+    }
+}
@@ -6,57 +6,248 @@
 //!
 //! ```
 
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::ids::DeviceId;
+use crate::name::{Name, NamePool};
+use crate::pci_ids::{parse_hex_u16, ParseError};
+
 /// A hardware vendor.
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+///
+/// Vendors are ordered by id, the remaining fields only break ties.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct Vendor {
     /// Vendor id
     id: u16,
-    name: String,
+    name: Name,
     devices: Vec<Device>,
+    comment: Option<String>,
 }
 
 impl Vendor {
     /// Create a new vendor with a given id and name.
+    ///
+    /// Any id is accepted, including `0xffff`, which pci.ids itself lists as "Illegal Vendor ID".
+    /// See [Vendor::try_new] for a constructor that rejects it.
     pub fn new(id: u16, name: String) -> Self {
         Self {
             id,
-            name,
+            name: name.into(),
             devices: Vec::new(),
+            comment: None,
         }
     }
 
+    /// Create a new vendor with a given id and name, rejecting `0xffff`.
+    ///
+    /// # Errors
+    /// Fails for `0xffff`, which is what reading the vendor id of an absent device returns and so
+    /// never identifies an actual vendor.
+    pub fn try_new(id: u16, name: String) -> Result<Self, &'static str> {
+        if id == 0xffff {
+            return Err("0xffff is not a valid vendor id");
+        }
+        Ok(Self::new(id, name))
+    }
+
     /// Unique vendor id.
     pub fn id(&self) -> u16 {
         self.id
     }
 
+    /// Identifier of the vendor as 4 digit lowercase hex, the way sysfs presents it minus the
+    /// `0x` prefix.
+    pub fn hex_id(&self) -> String {
+        format!("{:04x}", self.id)
+    }
+
     /// Name of the vendor.
     pub fn name(&self) -> &str {
         &self.name
     }
 
     /// List of devices the vendor has been ascribed.
+    ///
+    /// Prefer [Vendor::iter_devices], which doesn't tie callers to the storage being a [Vec].
     pub fn devices(&self) -> &Vec<Device> {
         &self.devices
     }
 
+    /// Iterate over the devices the vendor has been ascribed, in file order.
+    pub fn iter_devices(&self) -> impl Iterator<Item = &Device> {
+        self.devices.iter()
+    }
+
+    /// Returns the device with the given id.
+    pub fn device_by_id(&self, id: impl Into<DeviceId>) -> Option<&Device> {
+        let DeviceId(id) = id.into();
+        self.devices.iter().find(|d| d.id == id)
+    }
+
+    /// Whether the vendor has a device with the given id.
+    pub fn contains_device(&self, id: impl Into<DeviceId>) -> bool {
+        let DeviceId(id) = id.into();
+        self.devices.iter().any(|d| d.id == id)
+    }
+
+    /// Returns the devices whose names fuzzily match `query`, best match first, along with their
+    /// score. At most `limit` devices are returned.
+    ///
+    /// Meant for partial or slightly misspelled names, devices with equal scores keep their order
+    /// from the file.
+    #[cfg(feature = "fuzzy")]
+    pub fn fuzzy_find_devices(&self, query: &str, limit: usize) -> Vec<(&Device, i64)> {
+        use fuzzy_matcher::skim::SkimMatcherV2;
+        use fuzzy_matcher::FuzzyMatcher;
+
+        let matcher = SkimMatcherV2::default();
+        let mut matches: Vec<(&Device, i64)> = self
+            .devices
+            .iter()
+            .filter_map(|d| matcher.fuzzy_match(&d.name, query).map(|score| (d, score)))
+            .collect();
+        matches.sort_by_key(|&(_, score)| core::cmp::Reverse(score));
+        matches.truncate(limit);
+        matches
+    }
+
+    /// Ids of the vendor's devices, in file order.
+    pub fn device_ids(&self) -> Vec<u16> {
+        self.devices.iter().map(|d| d.id).collect()
+    }
+
+    /// Ids of the vendor's devices, in ascending order.
+    pub fn device_ids_sorted(&self) -> Vec<u16> {
+        let mut ids = self.device_ids();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Comment attached to the vendor in the pci.ids file.
+    ///
+    /// # Note
+    /// Comments are only kept when parsing with
+    /// [keep_comments](crate::pci_ids::ParseOptions::keep_comments) set, which also describes what
+    /// they are made of.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Set the comment to a given text.
+    pub(crate) fn set_comment(&mut self, comment: Option<String>) {
+        self.comment = comment;
+    }
+
     /// Set the devices to a given list of devices.
     pub(crate) fn set_devices(&mut self, devices: Vec<Device>) {
         self.devices = devices;
     }
+
+    /// Sort the devices and their subdevices by id, keeping entries with equal ids in order.
+    pub(crate) fn sort(&mut self) {
+        self.devices.sort_by_key(|d| d.id);
+        self.devices.iter_mut().for_each(Device::sort);
+    }
+
+    /// Heap memory owned by the vendor, its devices and their subdevices, in bytes.
+    pub(crate) fn heap_size(&self) -> usize {
+        self.name.heap_size()
+            + self.comment.as_ref().map_or(0, String::capacity)
+            + self.devices.capacity() * core::mem::size_of::<Device>()
+            + self.devices.iter().map(Device::heap_size).sum::<usize>()
+    }
+
+    /// Replace the names of the vendor, its devices and their subdevices by shared copies.
+    pub(crate) fn intern_names(&mut self, pool: &NamePool) {
+        pool.intern(&mut self.name);
+        self.devices.iter_mut().for_each(|d| d.intern_names(pool));
+    }
+
+    /// Merge another vendor with the same id into `self`, taking its name and merging or adding
+    /// its devices.
+    pub(crate) fn merge(&mut self, other: Vendor) {
+        self.name = other.name;
+        if other.comment.is_some() {
+            self.comment = other.comment;
+        }
+        for device in other.devices {
+            match self.devices.iter_mut().find(|d| d.id == device.id) {
+                Some(d) => d.merge(device),
+                None => self.devices.push(device),
+            }
+        }
+    }
+}
+
+impl fmt::Display for Vendor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04x} {}", self.id, self.name)
+    }
+}
+
+/// Iterate over the devices, like [Vendor::iter_devices].
+impl<'a> IntoIterator for &'a Vendor {
+    type Item = &'a Device;
+    type IntoIter = core::slice::Iter<'a, Device>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.devices.iter()
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedVendor {
+    /// Id of the vendor, like [Vendor::id].
+    pub fn id(&self) -> u16 {
+        self.id.to_native()
+    }
+
+    /// Name of the vendor, like [Vendor::name].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Devices of the vendor, in file order.
+    pub fn devices(&self) -> &[ArchivedDevice] {
+        &self.devices
+    }
+
+    /// Returns the first device with the given id, like [Vendor::device_by_id].
+    pub fn device_by_id(&self, id: impl Into<DeviceId>) -> Option<&ArchivedDevice> {
+        let DeviceId(id) = id.into();
+        self.devices.iter().find(|d| d.id() == id)
+    }
 }
 
 /// A PCI device.
 ///
+/// Devices are ordered by id, the remaining fields only break ties.
+///
 /// # Example
 /// ```
 ///
 /// ```
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct Device {
     id: u16,
-    name: String,
+    name: Name,
     subdevices: Vec<SubDevice>,
+    comment: Option<String>,
 }
 
 impl Device {
@@ -64,8 +255,9 @@ impl Device {
     pub fn new(id: u16, name: String) -> Self {
         Self {
             id,
-            name,
+            name: name.into(),
             subdevices: Vec::new(),
+            comment: None,
         }
     }
 
@@ -77,20 +269,230 @@ impl Device {
         self.id
     }
 
+    /// Identifier of the device as 4 digit lowercase hex, the way sysfs presents it minus the
+    /// `0x` prefix.
+    pub fn hex_id(&self) -> String {
+        format!("{:04x}", self.id)
+    }
+
     /// Name of the device.
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// Chip codename of the device, which is the part of the name before the first `[`, e.g.
+    /// `GP104` for `GP104 [GeForce GTX 1080]`.
+    ///
+    /// Returns `None` for names without a `[` or with nothing in front of it.
+    pub fn codename(&self) -> Option<&str> {
+        let (codename, _) = self.name.split_once('[')?;
+        Some(codename.trim()).filter(|c| !c.is_empty())
+    }
+
+    /// Marketing name of the device, which is the text inside the first pair of brackets, e.g.
+    /// `GeForce GTX 1080` for `GP104 [GeForce GTX 1080]`.
+    ///
+    /// Returns `None` for names without a complete pair of brackets.
+    pub fn marketing_name(&self) -> Option<&str> {
+        let (_, rest) = self.name.split_once('[')?;
+        let (marketing_name, _) = rest.split_once(']')?;
+        Some(marketing_name.trim())
+    }
+
     /// List of subdevices the device can be.
     pub fn subdevices(&self) -> &Vec<SubDevice> {
         &self.subdevices
     }
 
+    /// Comment attached to the device in the pci.ids file.
+    ///
+    /// # Note
+    /// Comments are only kept when parsing with
+    /// [keep_comments](crate::pci_ids::ParseOptions::keep_comments) set, which also describes what
+    /// they are made of.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Set the comment to a given text.
+    pub(crate) fn set_comment(&mut self, comment: Option<String>) {
+        self.comment = comment;
+    }
+
+    /// Returns the subdevice with the given subvendor and subdevice ids.
+    pub fn subdevice_by_ids(&self, subvendor_id: u16, subdevice_id: u16) -> Option<&SubDevice> {
+        self.subdevices
+            .iter()
+            .find(|s| s.subvendor_id == subvendor_id && s.subdevice_id == subdevice_id)
+    }
+
+    /// Look up a subdevice by its subvendor and subdevice ids, also accepting them swapped.
+    ///
+    /// The ids are tried as `(a, b)` first and as `(b, a)` second, the returned [IdOrder] tells
+    /// which one matched so a likely swapped input can be flagged.
+    pub fn subdevice_by_ids_either_order(&self, a: u16, b: u16) -> Option<(&SubDevice, IdOrder)> {
+        self.subdevice_by_ids(a, b)
+            .map(|s| (s, IdOrder::AsGiven))
+            .or_else(|| self.subdevice_by_ids(b, a).map(|s| (s, IdOrder::Swapped)))
+    }
+
     /// Set the subdevices to a given list of subdevices.
     pub(crate) fn set_subdevices(&mut self, subdevices: Vec<SubDevice>) {
         self.subdevices = subdevices;
     }
+
+    /// Sort the subdevices by subvendor id and then subdevice id, keeping duplicates in order.
+    pub(crate) fn sort(&mut self) {
+        self.subdevices
+            .sort_by_key(|s| (s.subvendor_id, s.subdevice_id));
+    }
+
+    /// Heap memory owned by the device and its subdevices, in bytes.
+    pub(crate) fn heap_size(&self) -> usize {
+        self.name.heap_size()
+            + self.comment.as_ref().map_or(0, String::capacity)
+            + self.subdevices.capacity() * core::mem::size_of::<SubDevice>()
+            + self
+                .subdevices
+                .iter()
+                .map(SubDevice::heap_size)
+                .sum::<usize>()
+    }
+
+    /// Replace the names of the device and its subdevices by shared copies.
+    pub(crate) fn intern_names(&mut self, pool: &NamePool) {
+        pool.intern(&mut self.name);
+        self.subdevices
+            .iter_mut()
+            .for_each(|s| s.intern_names(pool));
+    }
+
+    /// Merge another device with the same id into `self`, taking its name and replacing or adding
+    /// its subdevices.
+    pub(crate) fn merge(&mut self, other: Device) {
+        self.name = other.name;
+        if other.comment.is_some() {
+            self.comment = other.comment;
+        }
+        for subdevice in other.subdevices {
+            match self.subdevices.iter_mut().find(|s| {
+                s.subvendor_id == subdevice.subvendor_id && s.subdevice_id == subdevice.subdevice_id
+            }) {
+                Some(s) => *s = subdevice,
+                None => self.subdevices.push(subdevice),
+            }
+        }
+    }
+}
+
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04x} {}", self.id, self.name)
+    }
+}
+
+/// Iterate over the subdevices, in file order.
+impl<'a> IntoIterator for &'a Device {
+    type Item = &'a SubDevice;
+    type IntoIter = core::slice::Iter<'a, SubDevice>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.subdevices.iter()
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedDevice {
+    /// Id of the device, like [Device::id].
+    pub fn id(&self) -> u16 {
+        self.id.to_native()
+    }
+
+    /// Name of the device, like [Device::name].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Subdevices of the device, in file order.
+    pub fn subdevices(&self) -> &[ArchivedSubDevice] {
+        &self.subdevices
+    }
+}
+
+/// The subsystem vendor and device id pair identifying a [SubDevice], in the `1458:3763` notation
+/// used by lspci.
+///
+/// # Example
+/// ```
+/// use pci_id::vendor::SubsystemId;
+///
+/// let id: SubsystemId = "0x1458:3763".parse().unwrap();
+/// assert_eq!(id.subvendor_id(), 0x1458);
+/// assert_eq!(id.to_string(), "1458:3763");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct SubsystemId {
+    subvendor_id: u16,
+    subdevice_id: u16,
+}
+
+impl SubsystemId {
+    /// Create a new subsystem id from a given subvendor id and subdevice id.
+    pub fn new(subvendor_id: u16, subdevice_id: u16) -> Self {
+        Self {
+            subvendor_id,
+            subdevice_id,
+        }
+    }
+
+    /// Identifier of the OEM/subvendor.
+    pub fn subvendor_id(&self) -> u16 {
+        self.subvendor_id
+    }
+
+    /// Identifier of the actual device.
+    pub fn subdevice_id(&self) -> u16 {
+        self.subdevice_id
+    }
+}
+
+impl FromStr for SubsystemId {
+    type Err = ParseError;
+
+    /// Parse a `subvendor:subdevice` pair of hex ids, each of which may have a `0x` prefix and use
+    /// uppercase digits.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some((subvendor_id, subdevice_id)) if !subdevice_id.contains(':') => Ok(Self::new(
+                parse_hex_u16(subvendor_id)?,
+                parse_hex_u16(subdevice_id)?,
+            )),
+            _ => Err(ParseError::MalformedSubsystem {
+                subsystem: s.to_owned(),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for SubsystemId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04x}:{:04x}", self.subvendor_id, self.subdevice_id)
+    }
+}
+
+/// The order in which a pair of subsystem ids matched a [SubDevice].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IdOrder {
+    /// The ids matched as `(subvendor, subdevice)`, as they were given.
+    AsGiven,
+    /// The ids only matched as `(subdevice, subvendor)`, so they were most likely swapped.
+    Swapped,
 }
 
 /// A subset of a PCI device.
@@ -98,16 +500,24 @@ impl Device {
 /// Contains the name and id for a specific version of a device as well as the identifier for the
 /// OEM/subvendor/manufacturer that supplies the device.
 ///
+/// Subdevices are ordered by subvendor id, then subdevice id and then name.
+///
 /// # Example
 /// Look up all the cards that have Sapphire as a manufacturer. id = 0x1da2
 /// ```
 ///
 /// ```
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct SubDevice {
     subvendor_id: u16,
     subdevice_id: u16,
-    name: String,
+    name: Name,
+    comment: Option<String>,
 }
 
 impl SubDevice {
@@ -116,7 +526,8 @@ impl SubDevice {
         Self {
             subvendor_id,
             subdevice_id,
-            name,
+            name: name.into(),
+            comment: None,
         }
     }
 
@@ -139,6 +550,252 @@ impl SubDevice {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Comment attached to the subdevice in the pci.ids file.
+    ///
+    /// # Note
+    /// Comments are only kept when parsing with
+    /// [keep_comments](crate::pci_ids::ParseOptions::keep_comments) set, which also describes what
+    /// they are made of.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Set the comment to a given text.
+    pub(crate) fn set_comment(&mut self, comment: Option<String>) {
+        self.comment = comment;
+    }
+
+    /// Heap memory owned by the subdevice, in bytes.
+    pub(crate) fn heap_size(&self) -> usize {
+        self.name.heap_size() + self.comment.as_ref().map_or(0, String::capacity)
+    }
+
+    /// Replace the name by a shared copy.
+    pub(crate) fn intern_names(&mut self, pool: &NamePool) {
+        pool.intern(&mut self.name);
+    }
 }
 
+impl fmt::Display for SubDevice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04x} {:04x} {}",
+            self.subvendor_id, self.subdevice_id, self.name
+        )
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedSubDevice {
+    /// Id of the subvendor, like [SubDevice::subvendor_id].
+    pub fn subvendor_id(&self) -> u16 {
+        self.subvendor_id.to_native()
+    }
+
+    /// Id of the subdevice, like [SubDevice::subdevice_id].
+    pub fn subdevice_id(&self) -> u16 {
+        self.subdevice_id.to_native()
+    }
+
+    /// Name of the subdevice, like [SubDevice::name].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pci_ids::ParseError;
+    use crate::vendor::{Device, IdOrder, SubDevice, SubsystemId, Vendor};
+
+    /// Test that sorting orders by id, falling back to the name for equal subdevice ids
+    #[test]
+    fn test_ordering() {
+        let mut vendors = [
+            Vendor::new(0x8086, "Intel Corporation".to_owned()),
+            Vendor::new(0x1002, "Advanced Micro Devices, Inc. [AMD/ATI]".to_owned()),
+            Vendor::new(0x10de, "NVIDIA Corporation".to_owned()),
+            Vendor::new(0x0e11, "Compaq Computer Corporation".to_owned()),
+        ];
+        vendors.sort();
+        let ids: Vec<u16> = vendors.iter().map(|v| v.id()).collect();
+        assert_eq!(ids, [0x0e11, 0x1002, 0x10de, 0x8086]);
+
+        let mut devices = [
+            Device::new(0x1b81, "GP104 [GeForce GTX 1070]".to_owned()),
+            Device::new(0x1b80, "GP104 [GeForce GTX 1080]".to_owned()),
+        ];
+        devices.sort();
+        assert_eq!(devices[0].id(), 0x1b80);
+
+        let mut subdevices = [
+            SubDevice::new(0x1043, 0x8591, "b".to_owned()),
+            SubDevice::new(0x1043, 0x8591, "a".to_owned()),
+            SubDevice::new(0x1043, 0x8590, "c".to_owned()),
+        ];
+        subdevices.sort();
+        let names: Vec<&str> = subdevices.iter().map(|s| s.name()).collect();
+        assert_eq!(names, ["c", "a", "b"]);
+    }
+
+    /// Test that try_new rejects the invalid vendor id and accepts everything else
+    #[test]
+    fn test_vendor_try_new() {
+        assert!(Vendor::try_new(0xffff, "Illegal Vendor ID".to_owned()).is_err());
+        let vendor = Vendor::try_new(0x10de, "NVIDIA Corporation".to_owned()).unwrap();
+        assert_eq!(vendor.id(), 0x10de);
+        assert_eq!(vendor.name(), "NVIDIA Corporation");
+        assert_eq!(Vendor::new(0xffff, String::new()).id(), 0xffff);
+    }
+
+    /// Test that device ids come out in file order or sorted
+    #[test]
+    fn test_device_ids() {
+        let mut vendor = Vendor::new(0x10de, "NVIDIA Corporation".to_owned());
+        vendor.set_devices(vec![
+            Device::new(0x1b81, "GP104 [GeForce GTX 1070]".to_owned()),
+            Device::new(0x1b80, "GP104 [GeForce GTX 1080]".to_owned()),
+            Device::new(0x0020, "NV4 [Riva TNT]".to_owned()),
+        ]);
+        assert_eq!(vendor.device_ids(), [0x1b81, 0x1b80, 0x0020]);
+        assert_eq!(vendor.device_ids_sorted(), [0x0020, 0x1b80, 0x1b81]);
+    }
+
+    /// Test how codenames and marketing names are split out of device names
+    #[test]
+    fn test_codename_and_marketing_name() {
+        let device = Device::new(0x1b80, "GP104 [GeForce GTX 1080]".to_owned());
+        assert_eq!(device.codename(), Some("GP104"));
+        assert_eq!(device.marketing_name(), Some("GeForce GTX 1080"));
+
+        let device = Device::new(0x1234, "Navi 10 [Radeon RX 5700] [AMD]".to_owned());
+        assert_eq!(device.codename(), Some("Navi 10"));
+        assert_eq!(device.marketing_name(), Some("Radeon RX 5700"));
+
+        let device = Device::new(0x0046, "Smart Array 64xx".to_owned());
+        assert_eq!(device.codename(), None);
+        assert_eq!(device.marketing_name(), None);
+
+        let device = Device::new(0x0001, "[GeForce] unclosed [".to_owned());
+        assert_eq!(device.codename(), None);
+        assert_eq!(device.marketing_name(), Some("GeForce"));
+    }
+
+    /// Test that devices and subdevices of the real file are found by id
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_device_and_subdevice_by_id() {
+        use crate::pci_ids::{PciIds, PATH_TO_PCI_IDS};
+        use std::path::Path;
+
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let compaq = pci_ids.vendor_by_id(0x0e11).unwrap();
+        let device = compaq.device_by_id(0x0046).unwrap();
+        assert_eq!(device.name(), "Smart Array 64xx");
+        assert!(compaq.device_by_id(0xfffe).is_none());
 
+        let subdevice = device.subdevice_by_ids(0x0e11, 0x409a).unwrap();
+        assert_eq!(subdevice.name(), "Smart Array 641");
+        assert!(device.subdevice_by_ids(0x409a, 0x0e11).is_none());
+    }
+
+    /// Test that a misspelled query still finds the right devices
+    #[cfg(feature = "fuzzy")]
+    #[test]
+    fn test_fuzzy_find_devices() {
+        use crate::pci_ids::{PciIds, PATH_TO_PCI_IDS};
+        use std::path::Path;
+
+        let pci_ids = PciIds::parse_pci_id_list(Path::new(PATH_TO_PCI_IDS)).unwrap();
+        let amd = pci_ids.vendor_by_id(0x1002).unwrap();
+        let matches = amd.fuzzy_find_devices("radon rx", 20);
+        assert_eq!(matches.len(), 20);
+        assert!(matches.windows(2).all(|w| w[0].1 >= w[1].1));
+        assert!(matches.iter().any(|(d, _)| d.name().starts_with("Navi")));
+        assert!(matches.iter().all(|(d, _)| d.name().contains("Radeon")));
+        assert!(amd.fuzzy_find_devices("radon rx", 0).is_empty());
+    }
+
+    /// Test that hex ids keep their leading zeros
+    #[test]
+    fn test_hex_id() {
+        let vendor = Vendor::new(0x0e11, "Compaq Computer Corporation".to_owned());
+        assert_eq!(vendor.hex_id(), "0e11");
+        let device = Device::new(0x00a0, "Aries".to_owned());
+        assert_eq!(device.hex_id(), "00a0");
+        let device = Device::new(0x1B80, "GP104 [GeForce GTX 1080]".to_owned());
+        assert_eq!(device.hex_id(), "1b80");
+    }
+
+    /// Test that ids are printed zero padded in front of the name
+    #[test]
+    fn test_display() {
+        let vendor = Vendor::new(0x0e11, "Compaq Computer Corporation".to_owned());
+        assert_eq!(vendor.to_string(), "0e11 Compaq Computer Corporation");
+        let device = Device::new(0x0046, "Smart Array 64xx".to_owned());
+        assert_eq!(device.to_string(), "0046 Smart Array 64xx");
+        let subdevice = SubDevice::new(0x0e11, 0x409a, "Smart Array 641".to_owned());
+        assert_eq!(subdevice.to_string(), "0e11 409a Smart Array 641");
+    }
+
+    /// Test that swapped subsystem ids are found and reported as such
+    #[test]
+    fn test_subdevice_by_ids_either_order() {
+        let mut device = Device::new(0x0046, "Smart Array 64xx".to_owned());
+        device.set_subdevices(vec![
+            SubDevice::new(0x0e11, 0x409a, "Smart Array 641".to_owned()),
+            SubDevice::new(0x409b, 0x0e11, "Oddly numbered".to_owned()),
+        ]);
+
+        let (subdevice, order) = device
+            .subdevice_by_ids_either_order(0x0e11, 0x409a)
+            .unwrap();
+        assert_eq!(subdevice.name(), "Smart Array 641");
+        assert_eq!(order, IdOrder::AsGiven);
+
+        let (subdevice, order) = device
+            .subdevice_by_ids_either_order(0x409a, 0x0e11)
+            .unwrap();
+        assert_eq!(subdevice.name(), "Smart Array 641");
+        assert_eq!(order, IdOrder::Swapped);
+
+        let (_, order) = device
+            .subdevice_by_ids_either_order(0x409b, 0x0e11)
+            .unwrap();
+        assert_eq!(order, IdOrder::AsGiven);
+        assert!(device
+            .subdevice_by_ids_either_order(0x0e11, 0x0e11)
+            .is_none());
+    }
+
+    /// Test parsing and printing of subsystem id pairs
+    #[test]
+    fn test_subsystem_id() {
+        let id: SubsystemId = "1458:3763".parse().unwrap();
+        assert_eq!(id, SubsystemId::new(0x1458, 0x3763));
+        assert_eq!(" 0X1458:0x3763 ".parse::<SubsystemId>().unwrap(), id);
+        assert_eq!(
+            "1458:ABCD".parse::<SubsystemId>().unwrap().subdevice_id(),
+            0xabcd
+        );
+        assert_eq!(SubsystemId::new(0x0e11, 0x09).to_string(), "0e11:0009");
+
+        for input in ["1458", "1458:3763:0000", "1458-3763", ""] {
+            assert!(matches!(
+                input.parse::<SubsystemId>(),
+                Err(ParseError::MalformedSubsystem { .. })
+            ));
+        }
+        let err = "1458-3763".parse::<SubsystemId>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "malformed subsystem id `1458-3763`, expected `subvendor:subdevice`"
+        );
+        assert!(matches!(
+            "1458:37g3".parse::<SubsystemId>(),
+            Err(ParseError::InvalidId { .. })
+        ));
+    }
+}
@@ -0,0 +1,135 @@
+//! A single traversal over everything in a [PciIds](crate::pci_ids::PciIds), for computing
+//! arbitrary aggregates without writing the nested loops over and over.
+//!
+//! # Example
+//! Count the subdevices made by one subvendor:
+//! ```
+//! use pci_id::pci_ids::PciIds;
+//! use pci_id::vendor::{Device, SubDevice, Vendor};
+//! use pci_id::visitor::PciIdsVisitor;
+//!
+//! struct SapphireCounter(usize);
+//!
+//! impl PciIdsVisitor for SapphireCounter {
+//!     fn visit_subdevice(&mut self, _: &Vendor, _: &Device, subdevice: &SubDevice) {
+//!         if subdevice.subvendor_id() == 0x1da2 {
+//!             self.0 += 1;
+//!         }
+//!     }
+//! }
+//!
+//! let pci_ids = PciIds::parse_from_str(
+//!     "1002  Advanced Micro Devices, Inc. [AMD/ATI]\n\
+//!      \t67df  Ellesmere [Radeon RX 470/480/570/570X/580/580X/590]\n\
+//!      \t\t1da2 e353  Radeon RX 570 Pulse 4GB\n\
+//!      \t\t1462 3418  Radeon RX 580 Armor 4G OC\n\
+//!      \t731f  Navi 10 [Radeon RX 5600 OEM/5600 XT / 5700/5700 XT]\n\
+//!      \t\t1da2 e409  Sapphire Technology Limited Pulse Radeon RX 5700 XT\n",
+//! )
+//! .unwrap();
+//! let mut counter = SapphireCounter(0);
+//! pci_ids.visit(&mut counter);
+//! assert_eq!(counter.0, 2);
+//! ```
+
+use crate::class::{Class, Interface, SubClass};
+use crate::vendor::{Device, SubDevice, Vendor};
+
+/// Callbacks for [PciIds::visit](crate::pci_ids::PciIds::visit).
+///
+/// Every method does nothing by default, so only the levels of interest need to be implemented.
+/// Entries are visited depth first in file order, a parent always before its children, and all
+/// vendors before the classes. Each callback also receives the parents of the entry.
+pub trait PciIdsVisitor {
+    /// Called for every [Vendor].
+    fn visit_vendor(&mut self, _vendor: &Vendor) {}
+
+    /// Called for every [Device] of a vendor.
+    fn visit_device(&mut self, _vendor: &Vendor, _device: &Device) {}
+
+    /// Called for every [SubDevice] of a device.
+    fn visit_subdevice(&mut self, _vendor: &Vendor, _device: &Device, _subdevice: &SubDevice) {}
+
+    /// Called for every [Class].
+    fn visit_class(&mut self, _class: &Class) {}
+
+    /// Called for every [SubClass] of a class.
+    fn visit_subclass(&mut self, _class: &Class, _subclass: &SubClass) {}
+
+    /// Called for every programming [Interface] of a subclass.
+    fn visit_interface(&mut self, _class: &Class, _subclass: &SubClass, _interface: &Interface) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class::{Class, Interface, SubClass};
+    use crate::pci_ids::PciIds;
+    use crate::vendor::{Device, SubDevice, Vendor};
+    use crate::visitor::PciIdsVisitor;
+
+    /// Records the order entries were visited in
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl PciIdsVisitor for Recorder {
+        fn visit_vendor(&mut self, vendor: &Vendor) {
+            self.0.push(format!("vendor {:04x}", vendor.id()));
+        }
+
+        fn visit_device(&mut self, vendor: &Vendor, device: &Device) {
+            self.0
+                .push(format!("device {:04x} {:04x}", vendor.id(), device.id()));
+        }
+
+        fn visit_subdevice(&mut self, _: &Vendor, device: &Device, subdevice: &SubDevice) {
+            self.0.push(format!(
+                "subdevice {:04x} {:04x}",
+                device.id(),
+                subdevice.subdevice_id()
+            ));
+        }
+
+        fn visit_class(&mut self, class: &Class) {
+            self.0.push(format!("class {:02x}", class.id()));
+        }
+
+        fn visit_subclass(&mut self, _: &Class, subclass: &SubClass) {
+            self.0.push(format!("subclass {:02x}", subclass.id()));
+        }
+
+        fn visit_interface(&mut self, _: &Class, subclass: &SubClass, interface: &Interface) {
+            self.0.push(format!(
+                "interface {:02x} {:02x}",
+                subclass.id(),
+                interface.id()
+            ));
+        }
+    }
+
+    /// Test that every level is visited depth first with its parents
+    #[test]
+    fn test_visit_order() {
+        let data = "0e11  Compaq Computer Corporation\n\
+                    \t0046  Smart Array 64xx\n\
+                    \t\t0e11 409a  Smart Array 641\n\
+                    \t0049  NC7132 Gigabit Upgrade Module\n\
+                    C 0c  Serial bus controller\n\
+                    \t03  USB controller\n\
+                    \t\t30  XHCI\n";
+        let pci_ids = PciIds::parse_from_str(data).unwrap();
+        let mut recorder = Recorder::default();
+        pci_ids.visit(&mut recorder);
+        assert_eq!(
+            recorder.0,
+            [
+                "vendor 0e11",
+                "device 0e11 0046",
+                "subdevice 0046 409a",
+                "device 0e11 0049",
+                "class 0c",
+                "subclass 03",
+                "interface 03 30",
+            ]
+        );
+    }
+}